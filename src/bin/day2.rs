@@ -1,5 +1,7 @@
 use aoc_2025::assets::read_to_string;
+use std::fmt::{Display, Formatter};
 use std::ops::RangeInclusive;
+use std::time::Instant;
 
 #[derive(Debug)]
 struct ProductRange {
@@ -7,15 +9,84 @@ struct ProductRange {
     end: u64,
 }
 
+#[derive(Debug, PartialEq)]
+enum RangeListError {
+    EmptyEntry { offset: usize },
+    MissingSeparator { offset: usize },
+    InvalidId { offset: usize, value: String },
+    ReversedRange { offset: usize, begin: u64, end: u64 },
+    OverlappingRanges { offset: usize, other: usize },
+}
+
+impl Display for RangeListError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeListError::EmptyEntry { offset } => {
+                write!(f, "byte {offset}: empty entry between commas")
+            }
+            RangeListError::MissingSeparator { offset } => {
+                write!(f, "byte {offset}: expected a range like 11-22")
+            }
+            RangeListError::InvalidId { offset, value } => {
+                write!(f, "byte {offset}: {value:?} is not a valid id")
+            }
+            RangeListError::ReversedRange { offset, begin, end } => {
+                write!(
+                    f,
+                    "byte {offset}: range {begin}-{end} ends before it begins"
+                )
+            }
+            RangeListError::OverlappingRanges { offset, other } => write!(
+                f,
+                "byte {offset}: range overlaps the one at byte {other}, \
+                 merge them to avoid counting IDs twice"
+            ),
+        }
+    }
+}
+
+/// How `parse_range_list` reads a range list.
+#[derive(Debug, Clone, Copy)]
+struct RangeListOptions {
+    // the base the IDs are written in
+    radix: u32,
+    // merge overlapping ranges instead of rejecting them
+    merge_overlaps: bool,
+}
+
+impl Default for RangeListOptions {
+    fn default() -> Self {
+        RangeListOptions {
+            radix: 10,
+            merge_overlaps: false,
+        }
+    }
+}
+
 impl ProductRange {
-    fn new(raw_range: &str) -> ProductRange {
-        let mut r = raw_range.split("-");
-        let begin = r.next().unwrap();
-        let end = r.next().unwrap();
-        ProductRange {
-            begin: begin.parse().unwrap(),
-            end: end.parse().unwrap(),
+    /// Parses a range starting at byte `offset` of its list, whose IDs are written in
+    /// `radix`, allowing a `0x` prefix in hex.
+    fn parse(raw_range: &str, offset: usize, radix: u32) -> Result<ProductRange, RangeListError> {
+        let parse_id = |raw: &str, offset: usize| {
+            let value = raw.trim();
+            let digits = match radix {
+                16 => value.strip_prefix("0x").unwrap_or(value),
+                _ => value,
+            };
+            u64::from_str_radix(digits, radix).map_err(|_| RangeListError::InvalidId {
+                offset: offset + raw.len() - raw.trim_start().len(),
+                value: value.to_string(),
+            })
+        };
+        let (begin, end) = raw_range
+            .split_once("-")
+            .ok_or(RangeListError::MissingSeparator { offset })?;
+        let end_offset = offset + begin.len() + 1;
+        let (begin, end) = (parse_id(begin, offset)?, parse_id(end, end_offset)?);
+        if begin > end {
+            return Err(RangeListError::ReversedRange { offset, begin, end });
         }
+        Ok(ProductRange { begin, end })
     }
 
     fn get_range(&self) -> RangeInclusive<u64> {
@@ -24,13 +95,18 @@ impl ProductRange {
 }
 
 trait InvalidIndexDetector {
-    fn is_invalid_index(&self, index: &String) -> bool;
+    fn is_invalid_index(&self, index: &str) -> bool;
+
+    /// The base whose digits `is_invalid_index` is given.
+    fn radix(&self) -> u32 {
+        10
+    }
 }
 
 struct Problem1Detector;
 impl InvalidIndexDetector for Problem1Detector {
-    fn is_invalid_index(&self, index: &String) -> bool {
-        if index.len() % 2 != 0 {
+    fn is_invalid_index(&self, index: &str) -> bool {
+        if !index.len().is_multiple_of(2) {
             return false;
         }
         let half = index.len() / 2;
@@ -42,13 +118,13 @@ impl InvalidIndexDetector for Problem1Detector {
 
 struct Problem2Detector;
 impl InvalidIndexDetector for Problem2Detector {
-    fn is_invalid_index(&self, index: &String) -> bool {
+    fn is_invalid_index(&self, index: &str) -> bool {
         let total_len = index.len();
         let half = index.len() / 2;
         'check_pattern: for i in 0..half {
             let pattern_len = i + 1;
             // a quick length based check
-            if total_len % pattern_len != 0 {
+            if !total_len.is_multiple_of(pattern_len) {
                 continue;
             }
 
@@ -68,26 +144,289 @@ impl InvalidIndexDetector for Problem2Detector {
     }
 }
 
+/// Flags IDs made of a pattern of at least `min_pattern_len` digits repeated a number of
+/// times that falls in one of `repeats`, e.g. exactly 3 or anywhere from 2 to 5. Digits
+/// are those of the ID written in `radix`, anywhere from 2 to 36.
+#[derive(Debug, PartialEq)]
+struct RepetitionDetector {
+    repeats: Vec<RangeInclusive<u32>>,
+    min_pattern_len: u32,
+    radix: u32,
+}
+
+impl RepetitionDetector {
+    /// Parses a comma-separated list of repeat counts, each `N`, `A-B`, or `A-` for A or more.
+    fn parse(repeats_spec: &str, min_pattern_len: u32) -> Result<RepetitionDetector, String> {
+        let invalid = || format!("invalid repeat counts {repeats_spec:?}");
+        let count = |raw: &str| raw.trim().parse::<u32>().map_err(|_| invalid());
+        let mut repeats = Vec::new();
+        for part in repeats_spec.split(',') {
+            let counts = match part.split_once('-') {
+                Some((low, "")) => count(low)?..=u32::MAX,
+                Some((low, high)) => count(low)?..=count(high)?,
+                None => count(part)?..=count(part)?,
+            };
+            if *counts.start() < 2 || counts.is_empty() {
+                return Err(format!("{}: counts start at 2", invalid()));
+            }
+            repeats.push(counts);
+        }
+        Ok(RepetitionDetector {
+            repeats,
+            min_pattern_len,
+            radix: 10,
+        })
+    }
+
+    fn accepts(&self, pattern_len: u32, repeats: u32) -> bool {
+        pattern_len >= self.min_pattern_len && self.repeats.iter().any(|r| r.contains(&repeats))
+    }
+
+    fn sum_invalid(&self, ranges: &[ProductRange]) -> u128 {
+        ranges
+            .iter()
+            .map(|range| sum_repeated(range, self.radix, |len, repeats| self.accepts(len, repeats)))
+            .sum()
+    }
+}
+
+impl InvalidIndexDetector for RepetitionDetector {
+    fn is_invalid_index(&self, index: &str) -> bool {
+        let total_len = index.len();
+        (1..total_len)
+            .filter(|len| total_len.is_multiple_of(*len))
+            .filter(|len| self.accepts(*len as u32, (total_len / len) as u32))
+            .any(|len| {
+                index
+                    .as_bytes()
+                    .chunks(len)
+                    .all(|c| c == &index.as_bytes()[..len])
+            })
+    }
+
+    fn radix(&self) -> u32 {
+        self.radix
+    }
+}
+
+fn digit_count(mut value: u64, radix: u32) -> u32 {
+    let mut digits = 1;
+    while value >= radix as u64 {
+        value /= radix as u64;
+        digits += 1;
+    }
+    digits
+}
+
+fn to_radix_string(mut value: u64, radix: u32) -> String {
+    let mut digits = Vec::new();
+    loop {
+        digits.push(char::from_digit((value % radix as u64) as u32, radix).unwrap());
+        value /= radix as u64;
+        if value == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+// sum of the `total_len`-digit numbers in `range` that are some `pattern_len`-digit
+// pattern repeated: those are exactly the multiples p * 0..010..01 of the repeating
+// multiplier (e.g. 1001 for two 3-digit repeats), so only the bounds on p are needed
+fn sum_with_period(range: &ProductRange, radix: u32, total_len: u32, pattern_len: u32) -> u128 {
+    let radix = radix as u128;
+    let lowest = radix.pow(total_len - 1).max(range.begin as u128);
+    let highest = (radix.pow(total_len) - 1).min(range.end as u128);
+    if lowest > highest {
+        return 0;
+    }
+    let multiplier = (radix.pow(total_len) - 1) / (radix.pow(pattern_len) - 1);
+    let first = lowest.div_ceil(multiplier);
+    let last = highest / multiplier;
+    if first > last {
+        return 0;
+    }
+    // halve before multiplying, as a sum over all of u64 in binary nears u128::MAX
+    let count = last - first + 1;
+    let pattern_sum = if count.is_multiple_of(2) {
+        count / 2 * (first + last)
+    } else {
+        count * ((first + last) / 2)
+    };
+    multiplier * pattern_sum
+}
+
+// sum of the `total_len`-digit numbers in `range` having at least one of `periods`. A
+// number with periods a and b also has period gcd(a, b), so inclusion–exclusion over
+// the subsets of `periods` counts every number exactly once.
+fn sum_with_any_period(range: &ProductRange, radix: u32, total_len: u32, periods: &[u32]) -> u128 {
+    let gcd = |mut a: u32, mut b: u32| {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    };
+    let (mut added, mut removed) = (0, 0);
+    for subset in 1u32..1 << periods.len() {
+        let period = (0..periods.len())
+            .filter(|i| subset & 1 << i != 0)
+            .map(|i| periods[i])
+            .fold(0, gcd);
+        let sum = sum_with_period(range, radix, total_len, period);
+        if subset.count_ones() % 2 == 1 {
+            added += sum;
+        } else {
+            removed += sum;
+        }
+    }
+    added - removed
+}
+
+/// Sums the IDs in `range` whose digits in `radix` are a pattern repeated some number of
+/// times, for the (pattern length, repeat count) pairs `accepts` allows, without visiting
+/// every ID.
+fn sum_repeated(range: &ProductRange, radix: u32, accepts: impl Fn(u32, u32) -> bool) -> u128 {
+    let mut sum = 0;
+    for total_len in digit_count(range.begin, radix)..=digit_count(range.end, radix) {
+        let periods: Vec<u32> = (1..total_len)
+            .filter(|len| total_len.is_multiple_of(*len) && accepts(*len, total_len / len))
+            .collect();
+        sum += sum_with_any_period(range, radix, total_len, &periods);
+    }
+    sum
+}
+
+fn run_benchmark() {
+    let ranges = or_exit(
+        "day2.txt",
+        parse_product_ranges("day2.txt", &Default::default()),
+    );
+    let timed = |name: &str, run: &dyn Fn() -> u128| {
+        let start = Instant::now();
+        let sum = run();
+        println!("{name:>14}: {sum} in {:?}", start.elapsed());
+    };
+    timed("scan 1", &|| run_problem_1_scan(&ranges) as u128);
+    timed("arithmetic 1", &|| run_problem_1(&ranges));
+    timed("scan 2", &|| run_problem_2_scan(&ranges) as u128);
+    timed("arithmetic 2", &|| run_problem_2(&ranges));
+}
 
 fn main() {
-    let ranges = parse_product_ranges("day2.txt");
+    let mut args = std::env::args().skip(1);
+    let mut repeats_spec = None;
+    let mut min_pattern_len = 1;
+    let mut radix = 10;
+    let mut options = RangeListOptions::default();
+    let radix_arg = |name: &str, value: Option<String>| {
+        value
+            .and_then(|r| r.parse().ok())
+            .filter(|r| (2..=36).contains(r))
+            .unwrap_or_else(|| panic!("{name} needs a base from 2 to 36"))
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "bench" => return run_benchmark(),
+            "--repeats" => repeats_spec = args.next(),
+            "--min-pattern-len" => {
+                min_pattern_len = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .expect("--min-pattern-len needs a number")
+            }
+            "--radix" => radix = radix_arg("--radix", args.next()),
+            "--input-radix" => options.radix = radix_arg("--input-radix", args.next()),
+            "--merge-overlaps" => options.merge_overlaps = true,
+            other => panic!("unknown argument {other:?}"),
+        }
+    }
+
+    let ranges = or_exit("day2.txt", parse_product_ranges("day2.txt", &options));
+    if repeats_spec.is_some() || min_pattern_len > 1 || radix != 10 {
+        let detector = RepetitionDetector {
+            radix,
+            ..RepetitionDetector::parse(repeats_spec.as_deref().unwrap_or("2-"), min_pattern_len)
+                .unwrap_or_else(|e| panic!("{e}"))
+        };
+        println!("invalid ID sum: {}", detector.sum_invalid(&ranges));
+        return;
+    }
     println!("problem 1: {}", run_problem_1(&ranges));
     println!("problem 2: {}", run_problem_2(&ranges));
 }
 
-fn parse_product_ranges(file_name: &str) -> Vec<ProductRange> {
-    let mut ranges = Vec::new();
-    let data = read_to_string(file_name).unwrap();
-    for range in data.split(",") {
-        ranges.push(ProductRange::new(range))
+fn or_exit<T>(file_name: &str, parsed: Result<T, RangeListError>) -> T {
+    parsed.unwrap_or_else(|e| {
+        eprintln!("{file_name}: {e}");
+        std::process::exit(1);
+    })
+}
+
+fn parse_product_ranges(
+    file_name: &str,
+    options: &RangeListOptions,
+) -> Result<Vec<ProductRange>, RangeListError> {
+    parse_range_list(&read_to_string(file_name).unwrap(), options)
+}
+
+/// Parses comma-separated ranges, allowing whitespace and newlines around entries and a
+/// trailing comma. Overlapping ranges would have their shared IDs counted twice, so they
+/// are rejected unless `options` asks for them to be merged, which also sorts the list.
+fn parse_range_list(
+    data: &str,
+    options: &RangeListOptions,
+) -> Result<Vec<ProductRange>, RangeListError> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    let mut parts = data.split(",").peekable();
+    while let Some(part) = parts.next() {
+        let entry_offset = offset + part.len() - part.trim_start().len();
+        offset += part.len() + 1;
+        if part.trim().is_empty() {
+            if parts.peek().is_none() {
+                break;
+            }
+            return Err(RangeListError::EmptyEntry {
+                offset: entry_offset,
+            });
+        }
+        let range = ProductRange::parse(part.trim(), entry_offset, options.radix)?;
+        entries.push((entry_offset, range));
+    }
+
+    // sorted by begin, any overlap shows up between neighbours
+    let mut by_begin: Vec<&(usize, ProductRange)> = entries.iter().collect();
+    by_begin.sort_by_key(|(_, range)| range.begin);
+    let overlap = by_begin
+        .windows(2)
+        .find(|pair| pair[1].1.begin <= pair[0].1.end)
+        .map(|pair| (pair[1].0, pair[0].0));
+    match overlap {
+        None => Ok(entries.into_iter().map(|(_, range)| range).collect()),
+        Some((offset, other)) if !options.merge_overlaps => {
+            Err(RangeListError::OverlappingRanges { offset, other })
+        }
+        Some(_) => {
+            entries.sort_by_key(|(_, range)| range.begin);
+            let mut merged: Vec<ProductRange> = Vec::new();
+            for (_, range) in entries {
+                match merged.last_mut() {
+                    Some(last) if range.begin <= last.end => last.end = last.end.max(range.end),
+                    _ => merged.push(range),
+                }
+            }
+            Ok(merged)
+        }
     }
-    ranges
 }
 
-fn find_invalid_indexes(range: &ProductRange, invalid_range_detector: &impl InvalidIndexDetector) -> Vec<u64> {
+fn find_invalid_indexes(
+    range: &ProductRange,
+    invalid_range_detector: &impl InvalidIndexDetector,
+) -> Vec<u64> {
     let mut invalid_indexes = Vec::new();
     for index in range.get_range() {
-        let s = index.to_string();
+        let s = to_radix_string(index, invalid_range_detector.radix());
         if invalid_range_detector.is_invalid_index(&s) {
             invalid_indexes.push(index)
         }
@@ -96,10 +435,10 @@ fn find_invalid_indexes(range: &ProductRange, invalid_range_detector: &impl Inva
     invalid_indexes
 }
 
-fn run_problem(ranges: &Vec<ProductRange>, invalid_range_detector: &impl InvalidIndexDetector) -> u64 {
+fn run_problem(ranges: &[ProductRange], invalid_range_detector: &impl InvalidIndexDetector) -> u64 {
     let mut sum = 0;
     for range in ranges {
-       let invalid_indexes = find_invalid_indexes(range, invalid_range_detector);
+        let invalid_indexes = find_invalid_indexes(range, invalid_range_detector);
         for invalid in invalid_indexes {
             sum += invalid;
         }
@@ -107,30 +446,53 @@ fn run_problem(ranges: &Vec<ProductRange>, invalid_range_detector: &impl Invalid
     sum
 }
 
-fn run_problem_1(ranges: &Vec<ProductRange>) -> u64 {
-    run_problem(ranges, &Problem1Detector)
+fn run_problem_1(ranges: &[ProductRange]) -> u128 {
+    ranges
+        .iter()
+        .map(|range| sum_repeated(range, 10, |_, repeats| repeats == 2))
+        .sum()
 }
 
-fn run_problem_2(ranges: &Vec<ProductRange>) -> u64 {
-    run_problem(ranges, &Problem2Detector)
+fn run_problem_2(ranges: &[ProductRange]) -> u128 {
+    ranges
+        .iter()
+        .map(|range| sum_repeated(range, 10, |_, repeats| repeats >= 2))
+        .sum()
 }
 
+// the per-ID scans the arithmetic versions are checked against
+fn run_problem_1_scan(ranges: &[ProductRange]) -> u64 {
+    run_problem(ranges, &Problem1Detector)
+}
 
+fn run_problem_2_scan(ranges: &[ProductRange]) -> u64 {
+    run_problem(ranges, &Problem2Detector)
+}
 
 #[cfg(test)]
 mod production_range_test {
     use super::*;
+    use aoc_2025::rng::Rng;
+
+    impl ProductRange {
+        fn new(raw_range: &str) -> ProductRange {
+            ProductRange::parse(raw_range, 0, 10).unwrap()
+        }
+    }
 
     #[test]
     fn parse_range() {
         assert_eq!(ProductRange::new("11-22").get_range(), 11..=22);
-        assert_eq!(ProductRange::new("2121212118-2121212124").get_range(), 2121212118..=2121212124)
+        assert_eq!(
+            ProductRange::new("2121212118-2121212124").get_range(),
+            2121212118..=2121212124
+        )
     }
 
     #[test]
     fn invalid_index_problem_1() {
         let problem1 = Problem1Detector;
-        let is_invalid_index = |x: &String| { problem1.is_invalid_index(x)};
+        let is_invalid_index = |x: &String| problem1.is_invalid_index(x);
         assert!(is_invalid_index(&"11".to_string()));
         assert!(is_invalid_index(&"22".to_string()));
         assert!(is_invalid_index(&"1188511885".to_string()));
@@ -143,7 +505,7 @@ mod production_range_test {
     #[test]
     fn invalid_index_problem_2() {
         let detector = Problem2Detector;
-        let is_invalid_index = |x: &String| { detector.is_invalid_index(x)};
+        let is_invalid_index = |x: &String| detector.is_invalid_index(x);
         assert!(is_invalid_index(&"11".to_string()));
         assert!(is_invalid_index(&"22".to_string()));
         assert!(is_invalid_index(&"111".to_string()));
@@ -155,5 +517,217 @@ mod production_range_test {
         assert!(!is_invalid_index(&"123".to_string()));
     }
 
+    #[test]
+    fn arithmetic_matches_scan() {
+        let example = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,\
+            1698522-1698528,446443-446449,38593856-38593862,565653-565659,\
+            824824821-824824827,2121212118-2121212124";
+        let ranges: Vec<ProductRange> = example.split(',').map(ProductRange::new).collect();
+        assert_eq!(run_problem_1(&ranges), 1227775554);
+        assert_eq!(run_problem_2(&ranges), 4174379265);
+
+        for raw in [
+            "1-200000",
+            "999-1001",
+            "5-5",
+            "1111110-1111112",
+            "9999990-10000020",
+        ] {
+            let ranges = vec![ProductRange::new(raw)];
+            assert_eq!(
+                run_problem_1(&ranges),
+                run_problem_1_scan(&ranges) as u128,
+                "{raw}"
+            );
+            assert_eq!(
+                run_problem_2(&ranges),
+                run_problem_2_scan(&ranges) as u128,
+                "{raw}"
+            );
+        }
+
+        let mut rng = Rng::new(47);
+        for _ in 0..200 {
+            let digits = rng.range(1..=9) as u32;
+            let begin = rng.range(1..=10u64.pow(digits));
+            let ranges = vec![ProductRange {
+                begin,
+                end: begin + rng.range(0..=5000),
+            }];
+            assert_eq!(run_problem_1(&ranges), run_problem_1_scan(&ranges) as u128);
+            assert_eq!(run_problem_2(&ranges), run_problem_2_scan(&ranges) as u128);
+        }
+
+        // the whole u64 range takes no time at all
+        let everything = vec![ProductRange::new(&format!("1-{}", u64::MAX))];
+        assert!(run_problem_2(&everything) > run_problem_1(&everything));
+    }
+
+    #[test]
+    fn repetition_detector() {
+        let detector =
+            |spec: &str, min_pattern_len| RepetitionDetector::parse(spec, min_pattern_len).unwrap();
+        let exactly_three = detector("3", 1);
+        assert!(exactly_three.is_invalid_index("111"));
+        assert!(exactly_three.is_invalid_index("121212"));
+        assert!(exactly_three.is_invalid_index("111111"));
+        assert!(!exactly_three.is_invalid_index("1212"));
+        let long_patterns = detector("2-", 2);
+        assert!(long_patterns.is_invalid_index("1212"));
+        assert!(long_patterns.is_invalid_index("1111"));
+        assert!(!long_patterns.is_invalid_index("111"));
+
+        assert_eq!(detector("2-5, 7", 1).repeats, vec![2..=5, 7..=7]);
+        assert!(RepetitionDetector::parse("1-3", 1).is_err());
+        assert!(RepetitionDetector::parse("5-3", 1).is_err());
+        assert!(RepetitionDetector::parse("two", 1).is_err());
+
+        // exactly two and at least two are the two puzzle parts
+        let ranges: Vec<ProductRange> = ["1-200000", "38593856-38593862"]
+            .into_iter()
+            .map(ProductRange::new)
+            .collect();
+        assert_eq!(
+            detector("2", 1).sum_invalid(&ranges),
+            run_problem_1(&ranges)
+        );
+        assert_eq!(
+            detector("2-", 1).sum_invalid(&ranges),
+            run_problem_2(&ranges)
+        );
+
+        let mut rng = Rng::new(48);
+        for _ in 0..100 {
+            let digits = rng.range(1..=9) as u32;
+            let begin = rng.range(1..=10u64.pow(digits));
+            let ranges = vec![ProductRange {
+                begin,
+                end: begin + rng.range(0..=5000),
+            }];
+            let low = rng.range(2..=4);
+            let spec = format!("{low}-{}", low + rng.range(0..=2));
+            let random = detector(&spec, rng.range(1..=3) as u32);
+            assert_eq!(
+                random.sum_invalid(&ranges),
+                run_problem(&ranges, &random) as u128,
+                "{spec} from {begin}"
+            );
+        }
+    }
+
+    #[test]
+    fn other_bases() {
+        assert_eq!(to_radix_string(0, 2), "0");
+        assert_eq!(to_radix_string(0xabab, 16), "abab");
+        assert_eq!(to_radix_string(35, 36), "z");
+        assert_eq!(digit_count(0xff, 16), 2);
+        assert_eq!(digit_count(0x100, 16), 3);
 
+        let range = ProductRange::parse("0x1a-FF", 0, 16).unwrap();
+        assert_eq!(range.get_range(), 0x1a..=0xff);
+        assert_eq!(
+            ProductRange::parse("1010-1111", 0, 2).unwrap().get_range(),
+            10..=15
+        );
+
+        // 0x22, 0x33, ... 0xff are the doubled hex digits from 0x1a up
+        let hex = RepetitionDetector {
+            radix: 16,
+            ..RepetitionDetector::parse("2", 1).unwrap()
+        };
+        let doubled: u64 = (0x2..=0xf).map(|d| d * 0x11).sum();
+        assert_eq!(run_problem(&[range], &hex), doubled);
+        assert!(hex.is_invalid_index("abab"));
+
+        let mut rng = Rng::new(49);
+        for _ in 0..200 {
+            let radix = rng.range(2..=36) as u32;
+            let bits = rng.range(1..=32);
+            let begin = rng.range(1..=1 << bits);
+            let ranges = vec![ProductRange {
+                begin,
+                end: begin + rng.range(0..=3000),
+            }];
+            let detector = RepetitionDetector {
+                radix,
+                ..RepetitionDetector::parse("2-", rng.range(1..=2) as u32).unwrap()
+            };
+            assert_eq!(
+                detector.sum_invalid(&ranges),
+                run_problem(&ranges, &detector) as u128,
+                "base {radix} from {begin}"
+            );
+        }
+
+        // every u64 in binary sums without overflowing
+        let binary = RepetitionDetector {
+            radix: 2,
+            ..RepetitionDetector::parse("2-", 1).unwrap()
+        };
+        let everything = vec![ProductRange::new(&format!("1-{}", u64::MAX))];
+        assert!(binary.sum_invalid(&everything) > 0);
+    }
+
+    #[test]
+    fn tolerant_range_list() {
+        let options = RangeListOptions::default();
+        let ranges = parse_range_list(" 11-22,\n95 - 115 ,\r\n\t998-1012,\n", &options).unwrap();
+        let bounds: Vec<_> = ranges.iter().map(ProductRange::get_range).collect();
+        assert_eq!(bounds, vec![11..=22, 95..=115, 998..=1012]);
+        assert!(parse_range_list("", &options).unwrap().is_empty());
+
+        let error = |data| parse_range_list(data, &options).unwrap_err();
+        assert_eq!(
+            error("11-22,,95-115"),
+            RangeListError::EmptyEntry { offset: 6 }
+        );
+        assert_eq!(
+            error("11-22, 1122"),
+            RangeListError::MissingSeparator { offset: 7 }
+        );
+        assert_eq!(
+            error("11-22,95- 1x5"),
+            RangeListError::InvalidId {
+                offset: 10,
+                value: "1x5".to_string()
+            }
+        );
+        assert_eq!(
+            error("11-22,\n22-11"),
+            RangeListError::ReversedRange {
+                offset: 7,
+                begin: 22,
+                end: 11
+            }
+        );
+        assert_eq!(
+            error("1-10,30-40,5-20"),
+            RangeListError::OverlappingRanges {
+                offset: 11,
+                other: 0
+            }
+        );
+        assert_eq!(
+            error("22-11").to_string(),
+            "byte 0: range 22-11 ends before it begins"
+        );
+
+        // merged ranges count the shared IDs once
+        let merge = RangeListOptions {
+            merge_overlaps: true,
+            ..RangeListOptions::default()
+        };
+        let merged = parse_range_list("30-40,1-100,95-120,200-300", &merge).unwrap();
+        let bounds: Vec<_> = merged.iter().map(ProductRange::get_range).collect();
+        assert_eq!(bounds, vec![1..=120, 200..=300]);
+        assert_eq!(run_problem_1(&merged), run_problem_1_scan(&merged) as u128);
+        assert_eq!(run_problem_1(&merged), (11..=99).step_by(11).sum());
+
+        let hex = RangeListOptions {
+            radix: 16,
+            ..RangeListOptions::default()
+        };
+        let ranges = parse_range_list("0x1a-0xff, abab-abac", &hex).unwrap();
+        assert_eq!(ranges[1].get_range(), 0xabab..=0xabac);
+    }
 }
@@ -1,26 +1,150 @@
 use aoc_2025::assets::read_to_string;
+use aoc_2025::rng::Rng;
+use std::cmp::Reverse;
+use std::fmt::{Display, Formatter};
+use std::ops::AddAssign;
+use std::time::Instant;
 
 struct Battery {
-    jolt: Vec<u64>
+    // 1-based line of the bank in its input, for error reports
+    line: usize,
+    jolt: Vec<u64>,
 }
 
 impl Battery {
-    fn new(raw_data: &str) -> Battery {
+    fn parse(line: usize, raw_data: &str) -> Result<Battery, JoltageError> {
+        if raw_data.is_empty() {
+            return Err(JoltageError::EmptyBank { line });
+        }
         let mut jolt = Vec::new();
-        for c in raw_data.chars() {
-            jolt.push(c.to_digit(10).unwrap() as u64)
+        for (idx, c) in raw_data.chars().enumerate() {
+            let digit = c.to_digit(10).ok_or(JoltageError::InvalidBattery {
+                line,
+                column: idx + 1,
+                found: c,
+            })?;
+            jolt.push(digit as u64)
         }
 
-        Battery {
-            jolt
+        Ok(Battery { line, jolt })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum JoltageError {
+    InvalidTargetLen(i32),
+    EmptyBank {
+        line: usize,
+    },
+    InvalidBattery {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    BankTooShort {
+        line: usize,
+        len: usize,
+        needed: usize,
+    },
+}
+
+impl Display for JoltageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JoltageError::InvalidTargetLen(target_len) => {
+                write!(f, "target length must be positive, got {target_len}")
+            }
+            JoltageError::EmptyBank { line } => write!(f, "line {line}: empty battery bank"),
+            JoltageError::InvalidBattery {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {line}, column {column}: expected a digit, found {found:?}"
+            ),
+            JoltageError::BankTooShort { line, len, needed } => write!(
+                f,
+                "line {line}: bank has {len} batteries but the selection needs {needed}"
+            ),
+        }
+    }
+}
+
+/// What `sum_joltage` does with a bank too short for the selection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ShortBanks {
+    Abort,
+    Skip,
+}
+
+/// The total joltage of a run and the banks left out of it.
+#[derive(Debug, PartialEq)]
+struct JoltageSum {
+    total: Decimal,
+    skipped: Vec<JoltageError>,
+}
+
+/// An exact non-negative decimal number of any length, so joltages past 19 digits and
+/// sums of many of them don't overflow.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Decimal {
+    // least significant digit first, without leading zeros
+    digits: Vec<u8>,
+}
+
+impl Decimal {
+    fn from_digits(most_significant_first: impl DoubleEndedIterator<Item = u8>) -> Decimal {
+        let mut digits: Vec<u8> = most_significant_first.rev().collect();
+        while digits.last() == Some(&0) {
+            digits.pop();
         }
+        Decimal { digits }
     }
 
+    fn to_u64(&self) -> Option<u64> {
+        self.digits
+            .iter()
+            .rev()
+            .try_fold(0u64, |acc, &d| acc.checked_mul(10)?.checked_add(d as u64))
+    }
+}
+
+impl AddAssign<&Decimal> for Decimal {
+    fn add_assign(&mut self, other: &Decimal) {
+        if self.digits.len() < other.digits.len() {
+            self.digits.resize(other.digits.len(), 0);
+        }
+        let mut carry = 0;
+        for (idx, digit) in self.digits.iter_mut().enumerate() {
+            let sum = *digit + other.digits.get(idx).copied().unwrap_or(0) + carry;
+            *digit = sum % 10;
+            carry = sum / 10;
+            if carry == 0 && idx >= other.digits.len() {
+                break;
+            }
+        }
+        if carry > 0 {
+            self.digits.push(carry);
+        }
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+        for digit in self.digits.iter().rev() {
+            write!(f, "{digit}")?;
+        }
+        Ok(())
+    }
 }
 
 fn find_max_jolt(battery: &Battery, begin: usize, end: usize) -> (u64, usize) {
     let mut max = u64::MIN;
-    let mut max_idx = 0;
+    let mut max_idx = begin;
     for i in begin..end {
         if battery.jolt[i] > max {
             max = battery.jolt[i];
@@ -30,13 +154,13 @@ fn find_max_jolt(battery: &Battery, begin: usize, end: usize) -> (u64, usize) {
     (max, max_idx)
 }
 
+// reference implementation: picks each digit with a fresh window scan, O(n·k)
 fn find_joltage(battery: &Battery, target_len: usize) -> u64 {
     let vec = &battery.jolt;
     let jolt_len = vec.len();
     if jolt_len < target_len {
         panic!("can't find {target_len} digits from a too small vec");
     }
-    // optimize to use math instead of string for better performance
     let mut result = String::from("");
     let mut iter = 0;
     for i in 0..target_len {
@@ -48,37 +172,294 @@ fn find_joltage(battery: &Battery, target_len: usize) -> u64 {
     result.parse().unwrap()
 }
 
+/// The batteries turned on in a bank, in bank order, and the joltage they produce.
+#[derive(Debug, PartialEq)]
+struct Selection {
+    positions: Vec<usize>,
+    joltage: Decimal,
+}
+
+impl Selection {
+    fn new(battery: &Battery, positions: Vec<usize>) -> Selection {
+        let joltage = Decimal::from_digits(positions.iter().map(|&idx| battery.jolt[idx] as u8));
+        Selection { positions, joltage }
+    }
+
+    fn digit_sum(&self, battery: &Battery) -> u64 {
+        self.positions.iter().map(|&idx| battery.jolt[idx]).sum()
+    }
+
+    /// Renders the bank with the selected batteries in brackets.
+    fn highlight(&self, battery: &Battery) -> String {
+        let mut selected = self.positions.iter().peekable();
+        let mut line = String::new();
+        for (idx, digit) in battery.jolt.iter().enumerate() {
+            if selected.next_if_eq(&&idx).is_some() {
+                line += &format!("[{digit}]");
+            } else {
+                line += &digit.to_string();
+            }
+        }
+        line
+    }
+}
+
+/// What a bank's selection should optimise. Whatever the objective, the joltage produced
+/// is the selected digits read in bank order, and ties go to the earliest batteries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Objective {
+    Maximise,
+    Minimise,
+    // at least this many unselected batteries between any two selected ones
+    MaximiseWithGap(usize),
+    MaximiseDigitSum,
+}
+
+impl Objective {
+    /// Parses `max`, `min`, `gap:N` or `digit-sum`.
+    fn parse(spec: &str) -> Result<Objective, String> {
+        match spec {
+            "max" => Ok(Objective::Maximise),
+            "min" => Ok(Objective::Minimise),
+            "digit-sum" => Ok(Objective::MaximiseDigitSum),
+            _ => spec
+                .strip_prefix("gap:")
+                .and_then(|gap| gap.parse().ok())
+                .map(Objective::MaximiseWithGap)
+                .ok_or_else(|| format!("unknown objective {spec:?}")),
+        }
+    }
+
+    // the fewest batteries a bank needs for a selection of `target_len`
+    fn required_len(&self, target_len: usize) -> usize {
+        match *self {
+            Objective::MaximiseWithGap(gap) if target_len > 0 => (target_len - 1) * (gap + 1) + 1,
+            _ => target_len,
+        }
+    }
+
+    fn check_len(&self, battery: &Battery, target_len: usize) -> Result<(), JoltageError> {
+        let needed = self.required_len(target_len);
+        if battery.jolt.len() < needed {
+            return Err(JoltageError::BankTooShort {
+                line: battery.line,
+                len: battery.jolt.len(),
+                needed,
+            });
+        }
+        Ok(())
+    }
+
+    fn select(&self, battery: &Battery, target_len: usize) -> Selection {
+        match *self {
+            Objective::Maximise => select_batteries(battery, target_len),
+            Objective::Minimise => select_by_stack(battery, target_len, |top, digit| top > digit),
+            Objective::MaximiseWithGap(gap) => select_with_gap(battery, target_len, gap),
+            Objective::MaximiseDigitSum => select_largest_digits(battery, target_len),
+        }
+    }
+}
+
+/// Keeps the largest `target_len`-digit subsequence in a single pass: a digit evicts every
+/// smaller digit before it while there are still digits to spare, so the stack stays
+/// non-increasing over the part that gets kept. Equal digits are never evicted, so the
+/// earliest of several equally good batteries is chosen, as the window scan does.
+fn select_batteries(battery: &Battery, target_len: usize) -> Selection {
+    select_by_stack(battery, target_len, |top, digit| top < digit)
+}
+
+// the monotonic stack behind `select_batteries`, where `evicts(top, digit)` says whether
+// `digit` should replace `top` in the selection
+fn select_by_stack(
+    battery: &Battery,
+    target_len: usize,
+    evicts: impl Fn(u64, u64) -> bool,
+) -> Selection {
+    let jolt = &battery.jolt;
+    if jolt.len() < target_len {
+        panic!("can't find {target_len} digits from a too small vec");
+    }
+    let mut droppable = jolt.len() - target_len;
+    let mut stack: Vec<usize> = Vec::with_capacity(jolt.len());
+    for (idx, &digit) in jolt.iter().enumerate() {
+        while droppable > 0 && stack.last().is_some_and(|&top| evicts(jolt[top], digit)) {
+            stack.pop();
+            droppable -= 1;
+        }
+        stack.push(idx);
+    }
+    stack.truncate(target_len);
+    Selection::new(battery, stack)
+}
+
+// the window scan again, but each pick pushes the next window `gap` batteries further
+// along and keeps enough room behind it for the remaining picks and their gaps
+fn select_with_gap(battery: &Battery, target_len: usize, gap: usize) -> Selection {
+    let jolt_len = battery.jolt.len();
+    if target_len == 0 {
+        return Selection::new(battery, Vec::new());
+    }
+    let span = (target_len - 1) * (gap + 1) + 1;
+    if jolt_len < span {
+        panic!("can't find {target_len} digits {gap} apart from {jolt_len} batteries");
+    }
+    let mut positions = Vec::with_capacity(target_len);
+    let mut begin = 0;
+    for i in 0..target_len {
+        let max_end = jolt_len - (target_len - i - 1) * (gap + 1);
+        let (_, pos) = find_max_jolt(battery, begin, max_end);
+        positions.push(pos);
+        begin = pos + gap + 1;
+    }
+    Selection::new(battery, positions)
+}
+
+fn select_largest_digits(battery: &Battery, target_len: usize) -> Selection {
+    if battery.jolt.len() < target_len {
+        panic!("can't find {target_len} digits from a too small vec");
+    }
+    let mut order: Vec<usize> = (0..battery.jolt.len()).collect();
+    order.sort_by_key(|&idx| Reverse(battery.jolt[idx]));
+    order.truncate(target_len);
+    order.sort_unstable();
+    Selection::new(battery, order)
+}
+
+fn find_joltage_stack(battery: &Battery, target_len: usize) -> u64 {
+    select_batteries(battery, target_len)
+        .joltage
+        .to_u64()
+        .expect("joltage doesn't fit in u64")
+}
+
+fn generate_battery(rng: &mut Rng, len: usize) -> Battery {
+    Battery {
+        line: 0,
+        jolt: (0..len).map(|_| rng.range(1..=9)).collect(),
+    }
+}
+
+fn run_benchmark(bank_count: usize, bank_len: usize) {
+    let mut rng = Rng::new(3);
+    let batteries: Vec<Battery> = (0..bank_count)
+        .map(|_| generate_battery(&mut rng, bank_len))
+        .collect();
+    println!("{bank_count} banks of {bank_len} batteries");
+
+    let timed = |name: &str, find: fn(&Battery, usize) -> u64| {
+        let start = Instant::now();
+        let sum: u64 = batteries.iter().map(|b| find(b, 12)).sum();
+        println!("{name:>15}: {sum} in {:?}", start.elapsed());
+    };
+    timed("window scan", find_joltage);
+    timed("monotonic stack", find_joltage_stack);
+}
+
 fn main() {
-    let batteries = parse_batteries("day3.txt");
-    println!("problem 1: {}", sum_joltage(&batteries, 2));
-    println!("problem 2: {}", sum_joltage(&batteries, 12));
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mut objective = Objective::Maximise;
+    let mut short_banks = ShortBanks::Abort;
+    if let Some(idx) = args.iter().position(|a| a == "--skip-short") {
+        short_banks = ShortBanks::Skip;
+        args.remove(idx);
+    }
+    if let Some(idx) = args.iter().position(|a| a == "--objective") {
+        let spec = args.get(idx + 1).cloned().unwrap_or_default();
+        objective = Objective::parse(&spec).unwrap_or_else(|e| panic!("{e}"));
+        args.drain(idx..args.len().min(idx + 2));
+    }
+    if args.first().map(String::as_str) == Some("bench") {
+        let number = |idx: usize, default: usize| {
+            args.get(idx)
+                .map_or(default, |c| c.parse().expect("expected a number"))
+        };
+        run_benchmark(number(1, 1_000), number(2, 10_000));
+        return;
+    }
+
+    let batteries = or_exit(parse_batteries("day3.txt"));
+    if args.first().map(String::as_str) == Some("show") {
+        let target_len = args
+            .get(1)
+            .map_or(12, |t| t.parse().expect("expected a number"));
+        for battery in &batteries {
+            if let Err(e) = objective.check_len(battery, target_len) {
+                eprintln!("skipped {e}");
+                continue;
+            }
+            let selection = objective.select(battery, target_len);
+            print!("{} -> {}", selection.highlight(battery), selection.joltage);
+            if objective == Objective::MaximiseDigitSum {
+                print!(" (digit sum {})", selection.digit_sum(battery));
+            }
+            println!();
+        }
+        return;
+    }
+    for (problem, target_len) in [(1, 2), (2, 12)] {
+        let sum = or_exit(sum_joltage(&batteries, target_len, objective, short_banks));
+        for e in &sum.skipped {
+            eprintln!("problem {problem}: skipped {e}");
+        }
+        println!("problem {problem}: {}", sum.total);
+    }
 }
 
-fn parse_batteries(file_name: &str) -> Vec<Battery> {
-    let mut batteries = Vec::new();
+fn or_exit<T>(result: Result<T, JoltageError>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    })
+}
+
+fn parse_batteries(file_name: &str) -> Result<Vec<Battery>, JoltageError> {
     let data = read_to_string(file_name).unwrap();
-    for raw_joltage in data.lines() {
-        batteries.push(Battery::new(raw_joltage))
-    }
-    batteries
+    data.lines()
+        .enumerate()
+        .map(|(idx, raw_joltage)| Battery::parse(idx + 1, raw_joltage))
+        .collect()
 }
 
-fn sum_joltage(batteries: &Vec<Battery>, target_len: i32) -> u64 {
-    let mut sum = 0;
+fn sum_joltage(
+    batteries: &[Battery],
+    target_len: i32,
+    objective: Objective,
+    short_banks: ShortBanks,
+) -> Result<JoltageSum, JoltageError> {
+    if target_len <= 0 {
+        return Err(JoltageError::InvalidTargetLen(target_len));
+    }
+    let target_len = target_len as usize;
+    let mut sum = JoltageSum {
+        total: Decimal::default(),
+        skipped: Vec::new(),
+    };
     for battery in batteries {
-        sum += find_joltage(battery, target_len as usize);
+        match objective.check_len(battery, target_len) {
+            Ok(()) => sum.total += &objective.select(battery, target_len).joltage,
+            Err(e) if short_banks == ShortBanks::Skip => sum.skipped.push(e),
+            Err(e) => return Err(e),
+        }
     }
-    sum
+    Ok(sum)
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    impl Battery {
+        fn new(raw_data: &str) -> Battery {
+            Battery::parse(1, raw_data).unwrap()
+        }
+    }
+
     #[test]
     fn parse_battery() {
-        assert_eq!(Battery::new("9876543210").jolt, vec![9, 8, 7, 6, 5, 4, 3, 2, 1, 0])
+        assert_eq!(
+            Battery::new("9876543210").jolt,
+            vec![9, 8, 7, 6, 5, 4, 3, 2, 1, 0]
+        )
     }
 
     #[test]
@@ -89,7 +470,10 @@ mod tests {
 
         let s2 = "811111111111119";
         let battery2 = Battery::new(s2);
-        assert_eq!(find_max_jolt(&battery2,0, s2.len() - 1), (8, 0));
+        assert_eq!(find_max_jolt(&battery2, 0, s2.len() - 1), (8, 0));
+
+        // a window of zeros still reports a position inside the window
+        assert_eq!(find_max_jolt(&Battery::new("9000"), 1, 4), (0, 1));
     }
 
     #[test]
@@ -102,9 +486,293 @@ mod tests {
 
     #[test]
     fn test_find_joltage_12() {
-        assert_eq!(find_joltage(&Battery::new("987654321111111"), 12), 987654321111);
-        assert_eq!(find_joltage(&Battery::new("811111111111119"), 12), 811111111119);
-        assert_eq!(find_joltage(&Battery::new("234234234234278"), 12), 434234234278);
-        assert_eq!(find_joltage(&Battery::new("818181911112111"), 12), 888911112111);
+        assert_eq!(
+            find_joltage(&Battery::new("987654321111111"), 12),
+            987654321111
+        );
+        assert_eq!(
+            find_joltage(&Battery::new("811111111111119"), 12),
+            811111111119
+        );
+        assert_eq!(
+            find_joltage(&Battery::new("234234234234278"), 12),
+            434234234278
+        );
+        assert_eq!(
+            find_joltage(&Battery::new("818181911112111"), 12),
+            888911112111
+        );
+    }
+
+    #[test]
+    fn stack_matches_window_scan() {
+        let examples = [
+            "987654321111111",
+            "811111111111119",
+            "234234234234278",
+            "818181911112111",
+        ];
+        for raw in examples {
+            for target_len in [1, 2, 12, raw.len()] {
+                let battery = Battery::new(raw);
+                assert_eq!(
+                    find_joltage_stack(&battery, target_len),
+                    find_joltage(&battery, target_len)
+                );
+            }
+        }
+
+        let mut rng = Rng::new(42);
+        for _ in 0..500 {
+            let len = rng.range(1..=30) as usize;
+            let battery = generate_battery(&mut rng, len);
+            let target_len = rng.range(1..=len.min(19) as u64) as usize;
+            assert_eq!(
+                find_joltage_stack(&battery, target_len),
+                find_joltage(&battery, target_len),
+                "{:?} choosing {target_len}",
+                battery.jolt
+            );
+        }
+    }
+
+    #[test]
+    fn selected_positions() {
+        let battery = Battery::new("818181911112111");
+        let selection = select_batteries(&battery, 2);
+        assert_eq!(selection.positions, vec![6, 11]);
+        assert_eq!(selection.highlight(&battery), "818181[9]1111[2]111");
+
+        let battery = Battery::new("234234234234278");
+        let selection = select_batteries(&battery, 12);
+        assert_eq!(selection.joltage.to_u64(), Some(434234234278));
+        assert_eq!(
+            selection.highlight(&battery),
+            "23[4]2[3][4][2][3][4][2][3][4][2][7][8]"
+        );
+
+        // ties go to the earliest battery, like the window scan
+        let battery = Battery::new("9919");
+        assert_eq!(select_batteries(&battery, 2).positions, vec![0, 1]);
+        assert_eq!(select_batteries(&battery, 1).positions, vec![0]);
+
+        let mut rng = Rng::new(43);
+        for _ in 0..500 {
+            let len = rng.range(1..=30) as usize;
+            let battery = generate_battery(&mut rng, len);
+            let target_len = rng.range(1..=len.min(19) as u64) as usize;
+            let mut begin = 0;
+            let window_positions: Vec<usize> = (0..target_len)
+                .map(|i| {
+                    let (_, pos) = find_max_jolt(&battery, begin, len - (target_len - i - 1));
+                    begin = pos + 1;
+                    pos
+                })
+                .collect();
+            assert_eq!(
+                select_batteries(&battery, target_len).positions,
+                window_positions
+            );
+        }
+    }
+
+    #[test]
+    fn decimal_arithmetic() {
+        let decimal = |raw: &str| Decimal::from_digits(raw.bytes().map(|b| b - b'0'));
+        assert_eq!(decimal("000").to_string(), "0");
+        assert_eq!(decimal("0042").to_string(), "42");
+
+        let mut sum = decimal("999");
+        sum += &decimal("1");
+        assert_eq!(sum.to_string(), "1000");
+        sum += &decimal("123456");
+        assert_eq!(sum.to_string(), "124456");
+        sum += &Decimal::default();
+        assert_eq!(sum.to_u64(), Some(124456));
+
+        let mut big = decimal(&u64::MAX.to_string());
+        assert_eq!(big.to_u64(), Some(u64::MAX));
+        big += &decimal("1");
+        assert_eq!(big.to_string(), "18446744073709551616");
+        assert_eq!(big.to_u64(), None);
+
+        let mut rng = Rng::new(44);
+        for _ in 0..200 {
+            let (a, b) = (rng.next_u64() as u128, rng.next_u64() as u128);
+            let mut sum = decimal(&a.to_string());
+            sum += &decimal(&b.to_string());
+            assert_eq!(sum.to_string(), (a + b).to_string());
+        }
+    }
+
+    #[test]
+    fn joltage_beyond_u64() {
+        let raw = "98765432101234567890".repeat(3);
+        let battery = Battery::new(&raw);
+        assert_eq!(select_batteries(&battery, 60).joltage.to_string(), raw);
+        assert_eq!(
+            select_batteries(&battery, 25).joltage.to_string(),
+            "9999098765432101234567890"
+        );
+
+        // ten 19-digit joltages overflow a u64 sum
+        let nines: Vec<Battery> = (0..10).map(|_| Battery::new(&"9".repeat(19))).collect();
+        assert_eq!(
+            sum_joltage(&nines, 19, Objective::Maximise, ShortBanks::Abort)
+                .unwrap()
+                .total
+                .to_string(),
+            ((10u128.pow(19) - 1) * 10).to_string()
+        );
+
+        assert_eq!(
+            sum_joltage(&nines, 0, Objective::Maximise, ShortBanks::Abort),
+            Err(JoltageError::InvalidTargetLen(0))
+        );
+        assert_eq!(
+            sum_joltage(&nines, -3, Objective::Maximise, ShortBanks::Abort),
+            Err(JoltageError::InvalidTargetLen(-3))
+        );
+    }
+
+    // the best key over every feasible choice of `target_len` batteries
+    fn brute_force_best(
+        battery: &Battery,
+        target_len: usize,
+        feasible: impl Fn(&[usize]) -> bool,
+        key: impl Fn(&Selection) -> u64,
+        better: fn(u64, u64) -> bool,
+    ) -> Option<u64> {
+        let len = battery.jolt.len();
+        let mut best: Option<u64> = None;
+        for mask in 0u32..1 << len {
+            if mask.count_ones() as usize != target_len {
+                continue;
+            }
+            let positions: Vec<usize> = (0..len).filter(|i| mask & 1 << i != 0).collect();
+            if !feasible(&positions) {
+                continue;
+            }
+            let value = key(&Selection::new(battery, positions));
+            if best.is_none_or(|b| better(value, b)) {
+                best = Some(value);
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn objectives() {
+        let battery = Battery::new("818181911112111");
+        let show = |objective: Objective, target_len| {
+            let selection = objective.select(&battery, target_len);
+            (selection.highlight(&battery), selection.joltage.to_string())
+        };
+        assert_eq!(
+            show(Objective::Minimise, 3),
+            ("8[1]8[1]8[1]911112111".to_string(), "111".to_string())
+        );
+        assert_eq!(
+            show(Objective::MaximiseWithGap(2), 3),
+            ("818181[9]1111[2]11[1]".to_string(), "921".to_string())
+        );
+        assert_eq!(
+            show(Objective::MaximiseDigitSum, 3),
+            ("[8]1[8]181[9]11112111".to_string(), "889".to_string())
+        );
+        assert_eq!(Objective::parse("gap:3"), Ok(Objective::MaximiseWithGap(3)));
+        assert_eq!(Objective::parse("min"), Ok(Objective::Minimise));
+        assert!(Objective::parse("gap:").is_err());
+        assert!(Objective::parse("median").is_err());
+
+        let mut rng = Rng::new(45);
+        for _ in 0..300 {
+            let len = rng.range(1..=10) as usize;
+            let battery = Battery {
+                line: 1,
+                jolt: (0..len).map(|_| rng.range(0..=9)).collect(),
+            };
+            let target_len = rng.range(1..=len as u64) as usize;
+            let gap = rng.range(0..=2) as usize;
+            let value = |s: &Selection| s.joltage.to_u64().unwrap();
+            let spaced = |p: &[usize]| p.windows(2).all(|w| w[1] - w[0] > gap);
+
+            let max = Objective::Maximise.select(&battery, target_len);
+            let best = brute_force_best(&battery, target_len, |_| true, value, |a, b| a > b);
+            assert_eq!(Some(value(&max)), best);
+
+            let min = Objective::Minimise.select(&battery, target_len);
+            let best = brute_force_best(&battery, target_len, |_| true, value, |a, b| a < b);
+            assert_eq!(Some(value(&min)), best);
+
+            let digit_sum = Objective::MaximiseDigitSum.select(&battery, target_len);
+            let best = brute_force_best(
+                &battery,
+                target_len,
+                |_| true,
+                |s| s.digit_sum(&battery),
+                |a, b| a > b,
+            );
+            assert_eq!(Some(digit_sum.digit_sum(&battery)), best);
+
+            if (target_len - 1) * (gap + 1) < len {
+                let spread = Objective::MaximiseWithGap(gap).select(&battery, target_len);
+                assert!(spaced(&spread.positions), "{:?}", spread.positions);
+                let best = brute_force_best(&battery, target_len, spaced, value, |a, b| a > b);
+                assert_eq!(Some(value(&spread)), best);
+            }
+        }
+    }
+
+    #[test]
+    fn bank_validation() {
+        assert_eq!(
+            Battery::parse(3, "12\r3").err(),
+            Some(JoltageError::InvalidBattery {
+                line: 3,
+                column: 3,
+                found: '\r'
+            })
+        );
+        assert_eq!(
+            Battery::parse(4, "").err(),
+            Some(JoltageError::EmptyBank { line: 4 })
+        );
+        assert_eq!(
+            JoltageError::InvalidBattery {
+                line: 3,
+                column: 3,
+                found: '\r'
+            }
+            .to_string(),
+            "line 3, column 3: expected a digit, found '\\r'"
+        );
+
+        let batteries: Vec<Battery> = ["987", "12", "811111"]
+            .iter()
+            .enumerate()
+            .map(|(idx, raw)| Battery::parse(idx + 1, raw).unwrap())
+            .collect();
+        let too_short = JoltageError::BankTooShort {
+            line: 2,
+            len: 2,
+            needed: 3,
+        };
+        assert_eq!(
+            sum_joltage(&batteries, 3, Objective::Maximise, ShortBanks::Abort),
+            Err(too_short)
+        );
+        let sum = sum_joltage(&batteries, 3, Objective::Maximise, ShortBanks::Skip).unwrap();
+        assert_eq!(sum.total.to_u64(), Some(987 + 811));
+        assert_eq!(sum.skipped.len(), 1);
+
+        // the gap objective needs room for the gaps as well
+        let sum = sum_joltage(
+            &batteries,
+            2,
+            Objective::MaximiseWithGap(3),
+            ShortBanks::Skip,
+        );
+        assert_eq!(sum.unwrap().total.to_u64(), Some(81));
     }
 }
@@ -1,18 +1,265 @@
 use aoc_2025::assets::read_to_string;
+use aoc_2025::rng::Rng;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::time::Instant;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+enum Neighbourhood {
+    // the 8 surrounding cells
+    Moore,
+    // the 4 orthogonally adjacent cells
+    VonNeumann,
+    // every cell within this many rows and columns
+    Radius(i32),
+    // (row, col) offsets relative to the roll
+    Custom(Vec<(i32, i32)>),
+}
+
+impl Neighbourhood {
+    /// Parses `moore`, `von-neumann`, `radius:R` or `custom:DR,DC;DR,DC;...`.
+    fn parse(spec: &str) -> Result<Neighbourhood, String> {
+        match spec.split_once(':') {
+            None if spec == "moore" => Ok(Neighbourhood::Moore),
+            None if spec == "von-neumann" => Ok(Neighbourhood::VonNeumann),
+            Some(("radius", radius)) => radius
+                .parse()
+                .map(Neighbourhood::Radius)
+                .map_err(|_| format!("invalid radius {radius:?}")),
+            Some(("custom", offsets)) => offsets
+                .split(';')
+                .map(|offset| {
+                    let (r, c) = offset.split_once(',').unwrap_or((offset, ""));
+                    match (r.trim().parse(), c.trim().parse()) {
+                        (Ok(r), Ok(c)) => Ok((r, c)),
+                        _ => Err(format!("invalid offset {offset:?}, expected ROW,COL")),
+                    }
+                })
+                .collect::<Result<_, _>>()
+                .map(Neighbourhood::Custom),
+            _ => Err(format!("unknown neighbourhood {spec:?}")),
+        }
+    }
+
+    fn offsets(&self) -> Vec<(i32, i32)> {
+        let square = |radius: i32| -> Vec<(i32, i32)> {
+            (-radius..=radius)
+                .flat_map(|r| (-radius..=radius).map(move |c| (r, c)))
+                .collect()
+        };
+        let mut offsets = match self {
+            Neighbourhood::Moore => square(1),
+            Neighbourhood::VonNeumann => vec![(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Radius(radius) => square(*radius),
+            Neighbourhood::Custom(offsets) => offsets.clone(),
+        };
+        // a roll is never its own neighbour, and listing an offset twice doesn't count it twice
+        offsets.retain(|offset| *offset != (0, 0));
+        offsets.sort_unstable();
+        offsets.dedup();
+        offsets
+    }
+}
+
+/// What the cells beyond the edge of the grip count as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Boundary {
+    // nothing is stored outside the grip
+    Empty,
+    // the grip tiles the plane, so the edges wrap around
+    Torus,
+    // the warehouse walls count as occupied neighbours
+    Walls,
+}
+
+impl Boundary {
+    fn parse(spec: &str) -> Result<Boundary, String> {
+        match spec {
+            "empty" => Ok(Boundary::Empty),
+            "torus" => Ok(Boundary::Torus),
+            "walls" => Ok(Boundary::Walls),
+            other => Err(format!("unknown boundary {other:?}")),
+        }
+    }
+}
+
+/// A rectangle of cells, 0-based with inclusive corners.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Region {
+    top: i32,
+    left: i32,
+    bottom: i32,
+    right: i32,
+}
+
+impl Region {
+    /// Parses `TOP,LEFT,BOTTOM,RIGHT`.
+    fn parse(spec: &str) -> Result<Region, String> {
+        let corners: Vec<i32> = spec
+            .split(',')
+            .map(|v| v.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("invalid region {spec:?}, expected TOP,LEFT,BOTTOM,RIGHT"))?;
+        match corners[..] {
+            [top, left, bottom, right] if top <= bottom && left <= right => Ok(Region {
+                top,
+                left,
+                bottom,
+                right,
+            }),
+            _ => Err(format!(
+                "invalid region {spec:?}, expected TOP,LEFT,BOTTOM,RIGHT"
+            )),
+        }
+    }
+
+    fn contains(&self, row: i32, col: i32) -> bool {
+        (self.top..=self.bottom).contains(&row) && (self.left..=self.right).contains(&col)
+    }
+}
+
+/// The forklift rule: a roll can be moved when fewer than `movable_below` of the cells in
+/// its neighbourhood hold rolls. Only rolls inside `region`, when set, are ever removed,
+/// though rolls outside it still count as neighbours.
+#[derive(Debug, Clone)]
+struct RemovalRule {
+    offsets: Vec<(i32, i32)>,
+    movable_below: usize,
+    boundary: Boundary,
+    region: Option<Region>,
+}
+
+impl RemovalRule {
+    fn new(neighbourhood: &Neighbourhood, movable_below: usize) -> RemovalRule {
+        RemovalRule {
+            offsets: neighbourhood.offsets(),
+            movable_below,
+            boundary: Boundary::Empty,
+            region: None,
+        }
+    }
+
+    // the grip cell standing at (row, col), which may lie outside the grip on a torus
+    fn locate(&self, grip: &RollsGrip, row: i32, col: i32) -> Option<(i32, i32)> {
+        let (rows, cols) = (grip.rows() as i32, grip.cols() as i32);
+        match self.boundary {
+            Boundary::Torus => Some((row.rem_euclid(rows), col.rem_euclid(cols))),
+            _ => ((0..rows).contains(&row) && (0..cols).contains(&col)).then_some((row, col)),
+        }
+    }
+
+    fn can_remove(&self, row: i32, col: i32) -> bool {
+        self.region.is_none_or(|region| region.contains(row, col))
+    }
+
+    fn neighbours(&self, row: i32, col: i32) -> impl Iterator<Item = (i32, i32)> {
+        self.offsets.iter().map(move |(r, c)| (row + r, col + c))
+    }
+
+    // whether the bit-packed `BitGrip` can evaluate this rule
+    fn fits_bit_grip(&self) -> bool {
+        self.offsets == Neighbourhood::Moore.offsets()
+            && self.boundary == Boundary::Empty
+            && self.region.is_none()
+    }
+
+    // the cells that have (row, col) in their neighbourhood, which differ from
+    // `neighbours` when the offsets aren't symmetric
+    fn watchers(&self, row: i32, col: i32) -> impl Iterator<Item = (i32, i32)> {
+        self.offsets.iter().map(move |(r, c)| (row - r, col - c))
+    }
+}
+
+impl Default for RemovalRule {
+    fn default() -> Self {
+        RemovalRule::new(&Neighbourhood::Moore, 4)
+    }
+}
+
+// lines and columns are 1-based
+#[derive(Debug, PartialEq)]
+enum GripError {
+    EmptyGrip,
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    UnknownCharacter {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+impl Display for GripError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GripError::EmptyGrip => write!(f, "the grip has no cells"),
+            GripError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: expected {expected} cells like the first row, found {found}"
+            ),
+            GripError::UnknownCharacter {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {line}, column {column}: expected '@' or '.', found {found:?}"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 struct RollsGrip {
     grip: Vec<Vec<bool>>,
 }
 
 impl RollsGrip {
-    fn new(raw_grip_data: &str) -> RollsGrip {
-        let grip = raw_grip_data
+    fn new(raw_grip_data: &str) -> Result<RollsGrip, GripError> {
+        let mut grip: Vec<Vec<bool>> = Vec::new();
+        // a trailing newline (or a few) is fine, blank lines elsewhere are ragged rows
+        for (idx, line) in raw_grip_data
+            .trim_end_matches(['\r', '\n'])
             .lines()
-            .map(|line| line.chars().map(|c| c == '@').collect())
-            .collect();
+            .enumerate()
+        {
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(col_idx, c)| match c {
+                    '@' => Ok(true),
+                    '.' => Ok(false),
+                    found => Err(GripError::UnknownCharacter {
+                        line: idx + 1,
+                        column: col_idx + 1,
+                        found,
+                    }),
+                })
+                .collect::<Result<Vec<bool>, GripError>>()?;
 
-        RollsGrip { grip }
+            if let Some(first) = grip.first()
+                && first.len() != row.len()
+            {
+                return Err(GripError::RaggedRow {
+                    line: idx + 1,
+                    expected: first.len(),
+                    found: row.len(),
+                });
+            }
+            grip.push(row);
+        }
+
+        if grip.first().is_none_or(|row| row.is_empty()) {
+            return Err(GripError::EmptyGrip);
+        }
+        Ok(RollsGrip { grip })
     }
 
     fn rows(&self) -> usize {
@@ -20,7 +267,7 @@ impl RollsGrip {
     }
 
     fn cols(&self) -> usize {
-        self.grip.first().unwrap().len()
+        self.grip.first().map_or(0, |row| row.len())
     }
 
     fn is_roll_exist(&self, row_no: i32, col_no: i32) -> bool {
@@ -40,24 +287,341 @@ impl RollsGrip {
     }
 }
 
-fn is_roll_movable(grip: &RollsGrip, row: i32, col: i32) -> bool {
-    let surrounding_count = (row - 1..=row + 1)
-        .flat_map(|row| (col - 1..=col + 1).map(move |col| (row, col)))
-        .filter(|(r, c)| grip.is_roll_exist(*r, *c))
+/// The grip packed one bit per cell, row-major, 64 cells per word.
+///
+/// Only the Moore neighbourhood is supported: the eight neighbours of a whole word are
+/// the word itself and its rows above and below, shifted one column each way. Their
+/// per-cell counts are summed bit-sliced (bit `i` of `slices[k]` is bit `k` of cell `i`'s
+/// count), so 64 cells are classified at once without unpacking them.
+#[derive(Debug, Clone, PartialEq)]
+struct BitGrip {
+    rows: usize,
+    cols: usize,
+    words_per_row: usize,
+    // bits past the last column of each row are always zero
+    bits: Vec<u64>,
+}
+
+impl BitGrip {
+    fn new(rolls_grip: &RollsGrip) -> BitGrip {
+        let (rows, cols) = (rolls_grip.rows(), rolls_grip.cols());
+        let words_per_row = cols.div_ceil(64);
+        let mut bits = vec![0; rows * words_per_row];
+        for (row_idx, row) in rolls_grip.grip.iter().enumerate() {
+            for (col_idx, _) in row.iter().enumerate().filter(|(_, roll)| **roll) {
+                bits[row_idx * words_per_row + col_idx / 64] |= 1 << (col_idx % 64);
+            }
+        }
+        BitGrip {
+            rows,
+            cols,
+            words_per_row,
+            bits,
+        }
+    }
+
+    fn word(&self, row_idx: usize, word_idx: usize) -> u64 {
+        self.bits[row_idx * self.words_per_row + word_idx]
+    }
+
+    // the row's word with every cell replaced by its west and east neighbour
+    fn shifted(&self, row_idx: usize, word_idx: usize) -> (u64, u64) {
+        let word = self.word(row_idx, word_idx);
+        let prev = if word_idx > 0 {
+            self.word(row_idx, word_idx - 1)
+        } else {
+            0
+        };
+        let next = if word_idx + 1 < self.words_per_row {
+            self.word(row_idx, word_idx + 1)
+        } else {
+            0
+        };
+        ((word << 1) | (prev >> 63), (word >> 1) | (next << 63))
+    }
+
+    /// Rolls in this word with fewer than `movable_below` neighbouring rolls.
+    fn movable_mask(&self, row_idx: usize, word_idx: usize, movable_below: usize) -> u64 {
+        // rows outside the grip contribute empty words
+        let mut neighbours = [0u64; 8];
+        for (idx, r) in [row_idx.wrapping_sub(1), row_idx + 1]
+            .into_iter()
+            .enumerate()
+        {
+            if r < self.rows {
+                let (west, east) = self.shifted(r, word_idx);
+                neighbours[idx * 3..idx * 3 + 3].copy_from_slice(&[
+                    self.word(r, word_idx),
+                    west,
+                    east,
+                ]);
+            }
+        }
+        (neighbours[6], neighbours[7]) = self.shifted(row_idx, word_idx);
+
+        // ripple-carry add every neighbour mask into 4-bit counts (at most 8 neighbours)
+        let mut slices = [0u64; 4];
+        for mut carry in neighbours {
+            for slice in slices.iter_mut() {
+                let sum = *slice ^ carry;
+                carry &= *slice;
+                *slice = sum;
+            }
+        }
+
+        let below: u64 = (0..movable_below.min(9))
+            .map(|count| {
+                slices
+                    .iter()
+                    .enumerate()
+                    .fold(u64::MAX, |eq, (bit, slice)| {
+                        eq & if count >> bit & 1 == 1 {
+                            *slice
+                        } else {
+                            !*slice
+                        }
+                    })
+            })
+            .fold(0, |acc, eq| acc | eq);
+        below & self.word(row_idx, word_idx)
+    }
+
+    fn movable_masks(&self, movable_below: usize) -> Vec<u64> {
+        (0..self.rows)
+            .flat_map(|row_idx| {
+                (0..self.words_per_row)
+                    .map(move |word_idx| self.movable_mask(row_idx, word_idx, movable_below))
+            })
+            .collect()
+    }
+
+    fn run_problem_1(&self, movable_below: usize) -> usize {
+        self.movable_masks(movable_below)
+            .iter()
+            .map(|mask| mask.count_ones() as usize)
+            .sum()
+    }
+
+    fn run_problem_2(&mut self, movable_below: usize) -> usize {
+        let mut total_removed = 0;
+        loop {
+            let masks = self.movable_masks(movable_below);
+            let this_removed: usize = masks.iter().map(|mask| mask.count_ones() as usize).sum();
+            if this_removed == 0 {
+                break;
+            }
+            self.bits
+                .iter_mut()
+                .zip(masks)
+                .for_each(|(word, mask)| *word &= !mask);
+            total_removed += this_removed;
+        }
+        total_removed
+    }
+}
+
+fn count_neighbour_rolls(grip: &RollsGrip, rule: &RemovalRule, row: i32, col: i32) -> usize {
+    rule.neighbours(row, col)
+        .filter(|(r, c)| match rule.locate(grip, *r, *c) {
+            Some((r, c)) => grip.is_roll_exist(r, c),
+            None => rule.boundary == Boundary::Walls,
+        })
         .count()
-        - 1;
+}
 
-    surrounding_count < 4
+fn is_roll_movable(grip: &RollsGrip, rule: &RemovalRule, row: i32, col: i32) -> bool {
+    count_neighbour_rolls(grip, rule, row, col) < rule.movable_below
+}
+
+/// Draws the grip in the puzzle's notation, with the rolls in `removed` marked `x`.
+fn render_frame(rolls_grip: &RollsGrip, removed: &[(i32, i32)]) -> String {
+    let mut rows: Vec<Vec<char>> = rolls_grip
+        .grip
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|roll| if *roll { '@' } else { '.' })
+                .collect()
+        })
+        .collect();
+    removed
+        .iter()
+        .for_each(|(row_idx, col_idx)| rows[*row_idx as usize][*col_idx as usize] = 'x');
+    rows.iter()
+        .map(|row| format!("{}\n", row.iter().collect::<String>()))
+        .collect()
+}
+
+fn render_waves(rolls_grip: &mut RollsGrip, rule: &RemovalRule) -> String {
+    let mut frames = String::new();
+    remove_in_waves(rolls_grip, rule, |grip, wave| {
+        frames += &format!(
+            "Remove {} rolls of paper:\n{}\n",
+            wave.len(),
+            render_frame(grip, wave)
+        );
+    });
+    frames
+}
+
+// plain (ASCII) PBM, rolls are black
+fn to_pbm(rolls_grip: &RollsGrip) -> String {
+    let mut pbm = format!("P1\n{} {}\n", rolls_grip.cols(), rolls_grip.rows());
+    for row in &rolls_grip.grip {
+        let pixels: Vec<&str> = row
+            .iter()
+            .map(|roll| if *roll { "1" } else { "0" })
+            .collect();
+        pbm += &pixels.join(" ");
+        pbm.push('\n');
+    }
+    pbm
+}
+
+/// Writes `frame_0000.pbm` for the initial grip, then one image after every wave.
+/// Returns the number of frames written.
+fn write_pbm_frames(
+    rolls_grip: &mut RollsGrip,
+    rule: &RemovalRule,
+    output_dir: &Path,
+) -> std::io::Result<usize> {
+    std::fs::create_dir_all(output_dir)?;
+    let mut frames = vec![to_pbm(rolls_grip)];
+    remove_in_waves(rolls_grip, rule, |grip, wave| {
+        let mut after = grip.clone();
+        wave.iter()
+            .for_each(|(row_idx, col_idx)| after.remove(*row_idx, *col_idx));
+        frames.push(to_pbm(&after));
+    });
+
+    for (idx, frame) in frames.iter().enumerate() {
+        std::fs::write(output_dir.join(format!("frame_{idx:04}.pbm")), frame)?;
+    }
+    Ok(frames.len())
+}
+
+fn generate_grip(rng: &mut Rng, rows: usize, cols: usize) -> RollsGrip {
+    // roughly the density of the real puzzle input
+    let grip = (0..rows)
+        .map(|_| (0..cols).map(|_| rng.chance(2, 3)).collect())
+        .collect();
+    RollsGrip { grip }
+}
+
+fn run_benchmark(size: usize, rule: &RemovalRule) {
+    let grip = generate_grip(&mut Rng::new(4), size, size);
+
+    let start = Instant::now();
+    let rescan = run_problem_2_rescan(&mut grip.clone(), rule);
+    println!("    full rescan: {rescan} removed in {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let worklist = run_problem_2(&mut grip.clone(), rule);
+    println!(
+        "       worklist: {worklist} removed in {:?}",
+        start.elapsed()
+    );
+
+    if rule.fits_bit_grip() {
+        let start = Instant::now();
+        let movable = run_problem_1(&grip, rule);
+        println!(
+            "  per-cell scan: {movable} movable in {:?}",
+            start.elapsed()
+        );
+
+        let packed = BitGrip::new(&grip);
+        let start = Instant::now();
+        let movable = packed.run_problem_1(rule.movable_below);
+        println!(
+            "bit-sliced scan: {movable} movable in {:?}",
+            start.elapsed()
+        );
+
+        let start = Instant::now();
+        let packed = BitGrip::new(&grip).run_problem_2(rule.movable_below);
+        println!("     bit-packed: {packed} removed in {:?}", start.elapsed());
+    }
 }
 
 fn main() {
-    let input_file = "day4.txt";
-    let mut grip = RollsGrip::new(&read_to_string(input_file).unwrap());
-    println!("problem 1: {}", run_problem_1(&grip));
-    println!("problem 2: {}", run_problem_2(&mut grip));
+    let mut neighbourhood = Neighbourhood::Moore;
+    let mut movable_below = 4;
+    let mut positional = Vec::new();
+    let mut boundary = Boundary::Empty;
+    let mut region = None;
+    let mut show_waves = false;
+    let mut show_frames = false;
+    let mut pbm_dir = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--neighbourhood" => {
+                neighbourhood = Neighbourhood::parse(&args.next().unwrap_or_default())
+                    .unwrap_or_else(|e| panic!("{e}"))
+            }
+            "--threshold" => {
+                movable_below = args
+                    .next()
+                    .and_then(|t| t.parse().ok())
+                    .expect("--threshold needs a number")
+            }
+            "--boundary" => {
+                boundary = Boundary::parse(&args.next().unwrap_or_default())
+                    .unwrap_or_else(|e| panic!("{e}"))
+            }
+            "--region" => {
+                region = Some(
+                    Region::parse(&args.next().unwrap_or_default())
+                        .unwrap_or_else(|e| panic!("{e}")),
+                )
+            }
+            "--waves" => show_waves = true,
+            "--frames" => show_frames = true,
+            "--pbm" => pbm_dir = Some(args.next().expect("--pbm needs an output directory")),
+            _ => positional.push(arg),
+        }
+    }
+    let rule = RemovalRule {
+        boundary,
+        region,
+        ..RemovalRule::new(&neighbourhood, movable_below)
+    };
+
+    if positional.first().map(String::as_str) == Some("bench") {
+        let size = positional
+            .get(1)
+            .map_or(1000, |s| s.parse().expect("size must be a number"));
+        run_benchmark(size, &rule);
+        return;
+    }
+
+    let input_file = positional.first().map_or("day4.txt", String::as_str);
+    let mut grip = RollsGrip::new(&read_to_string(input_file).unwrap()).unwrap_or_else(|e| {
+        eprintln!("{input_file}: {e}");
+        std::process::exit(1);
+    });
+    let problem_1 = if rule.fits_bit_grip() {
+        BitGrip::new(&grip).run_problem_1(rule.movable_below)
+    } else {
+        run_problem_1(&grip, &rule)
+    };
+    println!("problem 1: {problem_1}");
+    if show_waves {
+        println!("waves: {:?}", removal_waves(&mut grip.clone(), &rule));
+    }
+    if show_frames {
+        print!("{}", render_waves(&mut grip.clone(), &rule));
+    }
+    if let Some(dir) = pbm_dir {
+        let written = write_pbm_frames(&mut grip.clone(), &rule, Path::new(&dir))
+            .unwrap_or_else(|e| panic!("can't write frames to {dir}: {e}"));
+        println!("wrote {written} frames to {dir}");
+    }
+    println!("problem 2: {}", run_problem_2(&mut grip, &rule));
 }
 
-fn find_movable_rolls(rolls_grip: &RollsGrip) -> Vec<(i32, i32)> {
+fn find_movable_rolls(rolls_grip: &RollsGrip, rule: &RemovalRule) -> Vec<(i32, i32)> {
     rolls_grip
         .grip
         .iter()
@@ -69,20 +633,22 @@ fn find_movable_rolls(rolls_grip: &RollsGrip) -> Vec<(i32, i32)> {
         })
         .filter(|(row_idx, col_idx)| {
             rolls_grip.is_roll_exist(*row_idx, *col_idx)
-                && is_roll_movable(rolls_grip, *row_idx, *col_idx)
+                && rule.can_remove(*row_idx, *col_idx)
+                && is_roll_movable(rolls_grip, rule, *row_idx, *col_idx)
         })
         .collect()
 }
 
-fn run_problem_1(rolls_grip: &RollsGrip) -> usize {
-    find_movable_rolls(rolls_grip).len()
+fn run_problem_1(rolls_grip: &RollsGrip, rule: &RemovalRule) -> usize {
+    find_movable_rolls(rolls_grip, rule).len()
 }
 
-fn run_problem_2(rolls_grip: &mut RollsGrip) -> usize {
+// rescans the whole grip on every wave, kept as the reference for `run_problem_2`
+fn run_problem_2_rescan(rolls_grip: &mut RollsGrip, rule: &RemovalRule) -> usize {
     let mut total_removed = 0;
 
     loop {
-        let to_be_removed = find_movable_rolls(rolls_grip);
+        let to_be_removed = find_movable_rolls(rolls_grip, rule);
         let this_removed = to_be_removed.len();
 
         to_be_removed
@@ -96,3 +662,390 @@ fn run_problem_2(rolls_grip: &mut RollsGrip) -> usize {
     }
     total_removed
 }
+
+/// Removes rolls wave by wave like `run_problem_2_rescan`, but keeps the neighbour count of
+/// every roll and only looks at the neighbours of the rolls removed in the previous wave.
+///
+/// Counts only ever go down, so a roll becomes movable exactly once: when its count drops
+/// to `movable_below - 1`. That's when it joins the next wave.
+fn run_problem_2(rolls_grip: &mut RollsGrip, rule: &RemovalRule) -> usize {
+    remove_in_waves(rolls_grip, rule, |_, _| {})
+}
+
+/// Number of rolls removed in each wave, in order.
+fn removal_waves(rolls_grip: &mut RollsGrip, rule: &RemovalRule) -> Vec<usize> {
+    let mut waves = Vec::new();
+    remove_in_waves(rolls_grip, rule, |_, wave| waves.push(wave.len()));
+    waves
+}
+
+// `on_wave` sees the grip right before each wave is removed, along with that wave
+fn remove_in_waves<F>(rolls_grip: &mut RollsGrip, rule: &RemovalRule, mut on_wave: F) -> usize
+where
+    F: FnMut(&RollsGrip, &[(i32, i32)]),
+{
+    let mut counts: Vec<Vec<usize>> = (0..rolls_grip.rows() as i32)
+        .map(|row| {
+            (0..rolls_grip.cols() as i32)
+                .map(|col| count_neighbour_rolls(rolls_grip, rule, row, col))
+                .collect()
+        })
+        .collect();
+
+    let mut total_removed = 0;
+    let mut wave = find_movable_rolls(rolls_grip, rule);
+    while !wave.is_empty() {
+        on_wave(rolls_grip, &wave);
+        total_removed += wave.len();
+        wave.iter()
+            .for_each(|(row_idx, col_idx)| rolls_grip.remove(*row_idx, *col_idx));
+
+        let mut next_wave = Vec::new();
+        for (row_idx, col_idx) in wave {
+            for (r, c) in rule.watchers(row_idx, col_idx) {
+                let Some((r, c)) = rule.locate(rolls_grip, r, c) else {
+                    continue;
+                };
+                if !rolls_grip.is_roll_exist(r, c) {
+                    continue;
+                }
+                let count = &mut counts[r as usize][c as usize];
+                *count -= 1;
+                if Some(*count) == rule.movable_below.checked_sub(1) && rule.can_remove(r, c) {
+                    next_wave.push((r, c));
+                }
+            }
+        }
+        wave = next_wave;
+    }
+    total_removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> RollsGrip {
+        RollsGrip::new(&read_to_string("day4_example.txt").unwrap()).unwrap()
+    }
+
+    fn solve(raw_grip_data: &str) -> (usize, usize) {
+        let rule = RemovalRule::default();
+        let mut grip = RollsGrip::new(raw_grip_data).unwrap();
+        (run_problem_1(&grip, &rule), run_problem_2(&mut grip, &rule))
+    }
+
+    #[test]
+    fn example_answers() {
+        let rule = RemovalRule::default();
+        assert_eq!(run_problem_1(&example(), &rule), 13);
+        assert_eq!(run_problem_2(&mut example(), &rule), 43);
+        assert_eq!(run_problem_2_rescan(&mut example(), &rule), 43);
+    }
+
+    #[test]
+    fn edge_rolls() {
+        // corners have 3 neighbours at most, so they always move
+        assert_eq!(solve("@@@\n@@@\n@@@"), (4, 9));
+        // the centre of a plus has exactly 4 neighbours and waits for the arms to go
+        assert_eq!(solve(".@.\n@@@\n.@."), (4, 5));
+        // edge cells in the middle of a full 3x4 block have 5 neighbours
+        assert_eq!(solve("@@@@\n@@@@\n@@@@"), (4, 12));
+        assert!(is_roll_movable(
+            &RollsGrip::new("@@\n@@").unwrap(),
+            &RemovalRule::default(),
+            0,
+            0
+        ));
+    }
+
+    #[test]
+    fn single_row_and_column() {
+        assert_eq!(solve("@"), (1, 1));
+        assert_eq!(solve("."), (0, 0));
+        assert_eq!(solve("@@@@@.@@"), (7, 7));
+        assert_eq!(solve("@\n@\n.\n@\n@\n@"), (5, 5));
+        assert_eq!(solve("@@@@@\n"), (5, 5));
+    }
+
+    #[test]
+    fn grip_validation() {
+        assert_eq!(RollsGrip::new("").unwrap_err(), GripError::EmptyGrip);
+        assert_eq!(RollsGrip::new("\n\n").unwrap_err(), GripError::EmptyGrip);
+        assert_eq!(
+            RollsGrip::new("@@.\n@.\n@@.").unwrap_err(),
+            GripError::RaggedRow {
+                line: 2,
+                expected: 3,
+                found: 2
+            }
+        );
+        assert_eq!(
+            RollsGrip::new("@@.\n\n@@.").unwrap_err(),
+            GripError::RaggedRow {
+                line: 2,
+                expected: 3,
+                found: 0
+            }
+        );
+        assert_eq!(
+            RollsGrip::new("@@.\n@x.").unwrap_err(),
+            GripError::UnknownCharacter {
+                line: 2,
+                column: 2,
+                found: 'x'
+            }
+        );
+        assert!(RollsGrip::new("@@.\r\n.@@\r\n").is_ok());
+    }
+
+    #[test]
+    fn worklist_matches_rescan() {
+        let rule = RemovalRule::default();
+        let mut rng = Rng::new(36);
+        for _ in 0..50 {
+            let rows = rng.range(1..=30) as usize;
+            let cols = rng.range(1..=30) as usize;
+            let grip = generate_grip(&mut rng, rows, cols);
+            let mut worklist = grip.clone();
+            let mut rescan = grip.clone();
+            assert_eq!(
+                run_problem_2(&mut worklist, &rule),
+                run_problem_2_rescan(&mut rescan, &rule)
+            );
+            assert_eq!(worklist.grip, rescan.grip);
+        }
+    }
+
+    #[test]
+    fn configurable_rules() {
+        let example = example();
+        let moore = RemovalRule::default();
+        for same_as_moore in [
+            Neighbourhood::Radius(1),
+            Neighbourhood::Custom(vec![
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 0),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+                (1, 1),
+            ]),
+        ] {
+            let rule = RemovalRule::new(&same_as_moore, 4);
+            assert_eq!(rule.offsets, moore.offsets);
+            assert_eq!(run_problem_1(&example, &rule), 13);
+        }
+
+        // with only 4 neighbours, a threshold of 5 frees every roll at once
+        let von_neumann = RemovalRule::new(&Neighbourhood::VonNeumann, 5);
+        assert_eq!(run_problem_1(&example, &von_neumann), 71);
+        assert_eq!(
+            run_problem_1(&example, &RemovalRule::new(&Neighbourhood::Moore, 0)),
+            0
+        );
+
+        // asymmetric neighbourhoods are where the worklist has to follow `watchers`
+        let mut rng = Rng::new(37);
+        for _ in 0..50 {
+            let offsets = (0..rng.range(1..=6))
+                .map(|_| (rng.range(0..=4) as i32 - 2, rng.range(0..=4) as i32 - 2))
+                .collect();
+            let rule = RemovalRule::new(&Neighbourhood::Custom(offsets), rng.range(0..=4) as usize);
+            let grip = generate_grip(&mut rng, 20, 20);
+            let mut worklist = grip.clone();
+            let mut rescan = grip.clone();
+            assert_eq!(
+                run_problem_2(&mut worklist, &rule),
+                run_problem_2_rescan(&mut rescan, &rule)
+            );
+            assert_eq!(worklist.grip, rescan.grip);
+        }
+    }
+
+    #[test]
+    fn parse_neighbourhood() {
+        assert_eq!(Neighbourhood::parse("moore"), Ok(Neighbourhood::Moore));
+        assert_eq!(
+            Neighbourhood::parse("von-neumann"),
+            Ok(Neighbourhood::VonNeumann)
+        );
+        assert_eq!(
+            Neighbourhood::parse("radius:2"),
+            Ok(Neighbourhood::Radius(2))
+        );
+        assert_eq!(
+            Neighbourhood::parse("custom:-1,0; 1,0"),
+            Ok(Neighbourhood::Custom(vec![(-1, 0), (1, 0)]))
+        );
+        assert!(Neighbourhood::parse("custom:1").is_err());
+        assert!(Neighbourhood::parse("hexagonal").is_err());
+    }
+
+    #[test]
+    fn waves_and_frames() {
+        let rule = RemovalRule::default();
+        let example = example();
+        assert_eq!(
+            removal_waves(&mut example.clone(), &rule),
+            vec![13, 12, 7, 5, 2, 1, 1, 1, 1]
+        );
+
+        let frames = render_waves(&mut example.clone(), &rule);
+        assert!(frames.starts_with(
+            "Remove 13 rolls of paper:
+..xx.xx@x.
+x@@.@.@.@@
+@@@@@.x.@@
+@.@@@@..@.
+x@.@@@@.@x
+.@@@@@@@.@
+.@.@.@.@@@
+x.@@@.@@@@
+.@@@@@@@@.
+x.x.@@@.x.
+
+Remove 12 rolls of paper:
+.......x..
+.@@.x.x.@x
+"
+        ));
+
+        let dir = std::env::temp_dir().join(format!("day4_frames_{}", std::process::id()));
+        assert_eq!(
+            write_pbm_frames(&mut example.clone(), &rule, &dir).unwrap(),
+            10
+        );
+        let first = std::fs::read_to_string(dir.join("frame_0000.pbm")).unwrap();
+        assert!(first.starts_with("P1\n10 10\n0 0 1 1 0 1 1 1 1 0\n"));
+        let last = std::fs::read_to_string(dir.join("frame_0009.pbm")).unwrap();
+        let remaining: usize = last.lines().skip(2).map(|l| l.matches('1').count()).sum();
+        assert_eq!(remaining, 71 - 43);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn bit_packed_matches_reference() {
+        let example = example();
+        assert_eq!(BitGrip::new(&example).run_problem_1(4), 13);
+        assert_eq!(BitGrip::new(&example).run_problem_2(4), 43);
+
+        // widths around the 64-bit word boundary exercise the carries between words
+        let mut rng = Rng::new(39);
+        for cols in [1, 2, 63, 64, 65, 127, 128, 130] {
+            for movable_below in 0..=9 {
+                let rows = rng.range(1..=12) as usize;
+                let mut grip = generate_grip(&mut rng, rows, cols);
+                let rule = RemovalRule::new(&Neighbourhood::Moore, movable_below);
+                let mut packed = BitGrip::new(&grip);
+                assert_eq!(
+                    packed.run_problem_1(movable_below),
+                    run_problem_1(&grip, &rule)
+                );
+                assert_eq!(
+                    packed.run_problem_2(movable_below),
+                    run_problem_2(&mut grip, &rule)
+                );
+                assert_eq!(packed, BitGrip::new(&grip));
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_modes() {
+        let with_boundary = |boundary| RemovalRule {
+            boundary,
+            ..RemovalRule::default()
+        };
+        let torus = with_boundary(Boundary::Torus);
+        let walls = with_boundary(Boundary::Walls);
+
+        // on a torus a full grip gives every roll 8 neighbours
+        let full = RollsGrip::new("@@@@\n@@@@\n@@@@\n@@@@").unwrap();
+        assert_eq!(run_problem_1(&full, &torus), 0);
+        // against walls a lone row has 6 occupied neighbours per roll, 5 at the ends
+        let row = RollsGrip::new("@@@@@").unwrap();
+        assert_eq!(run_problem_1(&row, &walls), 0);
+        assert_eq!(run_problem_1(&row, &RemovalRule::default()), 5);
+        let corner = RollsGrip::new("@.\n..").unwrap();
+        assert_eq!(run_problem_1(&corner, &walls), 0);
+        assert_eq!(
+            run_problem_1(&corner, &RemovalRule::new(&Neighbourhood::Moore, 6)),
+            1
+        );
+
+        let mut rng = Rng::new(41);
+        for boundary in [Boundary::Torus, Boundary::Walls] {
+            for _ in 0..30 {
+                let grip = generate_grip(&mut rng, 12, 9);
+                let top = rng.range(0..=11) as i32;
+                let left = rng.range(0..=8) as i32;
+                let rule = RemovalRule {
+                    boundary,
+                    region: Some(Region {
+                        top,
+                        left,
+                        bottom: top + rng.range(0..=5) as i32,
+                        right: left + rng.range(0..=5) as i32,
+                    }),
+                    ..RemovalRule::new(&Neighbourhood::Radius(rng.range(1..=2) as i32), 7)
+                };
+                let mut worklist = grip.clone();
+                let mut rescan = grip.clone();
+                assert_eq!(
+                    run_problem_2(&mut worklist, &rule),
+                    run_problem_2_rescan(&mut rescan, &rule)
+                );
+                assert_eq!(worklist.grip, rescan.grip);
+            }
+        }
+    }
+
+    #[test]
+    fn torus_matches_tiled_warehouse() {
+        // the centre tile of a 3x3 tiling, with removal limited to it, sees exactly
+        // the neighbours a torus would give it
+        let tile = example();
+        let tiled_rows: Vec<Vec<bool>> = (0..3 * tile.rows())
+            .map(|r| tile.grip[r % tile.rows()].repeat(3))
+            .collect();
+        let tiled = RollsGrip { grip: tiled_rows };
+        let centre = RemovalRule {
+            region: Some(Region {
+                top: tile.rows() as i32,
+                left: tile.cols() as i32,
+                bottom: 2 * tile.rows() as i32 - 1,
+                right: 2 * tile.cols() as i32 - 1,
+            }),
+            ..RemovalRule::default()
+        };
+        let torus = RemovalRule {
+            boundary: Boundary::Torus,
+            ..RemovalRule::default()
+        };
+        assert_eq!(run_problem_1(&tiled, &centre), run_problem_1(&tile, &torus));
+        assert_eq!(run_problem_1(&tile, &torus), 2);
+    }
+
+    #[test]
+    fn parse_boundary_and_region() {
+        assert_eq!(Boundary::parse("torus"), Ok(Boundary::Torus));
+        assert_eq!(Boundary::parse("walls"), Ok(Boundary::Walls));
+        assert!(Boundary::parse("mirror").is_err());
+        assert_eq!(
+            Region::parse("1, 2,3,4"),
+            Ok(Region {
+                top: 1,
+                left: 2,
+                bottom: 3,
+                right: 4
+            })
+        );
+        assert!(Region::parse("3,0,1,0").is_err());
+        assert!(Region::parse("1,2,3").is_err());
+    }
+}
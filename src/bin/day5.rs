@@ -1,27 +1,337 @@
-use aoc_2025::assets::read_to_string;
+use aoc_2025::assets::open_assets;
+use aoc_2025::rng::Rng;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, Cursor};
+use std::time::Instant;
+
+// line numbers are 1-based and count from the top of the file
+#[derive(Debug, PartialEq)]
+enum DatabaseError {
+    MissingSeparator { line: usize },
+    InvalidId { line: usize, value: String },
+    ReversedRange { line: usize, begin: u64, end: u64 },
+    Unreadable { line: usize, reason: String },
+}
+
+impl Display for DatabaseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatabaseError::MissingSeparator { line } => {
+                write!(f, "line {line}: expected a range like 3-5")
+            }
+            DatabaseError::InvalidId { line, value } => {
+                write!(f, "line {line}: {value:?} is not a valid id")
+            }
+            DatabaseError::ReversedRange { line, begin, end } => {
+                write!(f, "line {line}: range {begin}-{end} ends before it begins")
+            }
+            DatabaseError::Unreadable { line, reason } => {
+                write!(f, "line {line}: can't read the database: {reason}")
+            }
+        }
+    }
+}
+
+fn parse_id(raw_id: &str, line: usize) -> Result<u64, DatabaseError> {
+    raw_id.trim().parse().map_err(|_| DatabaseError::InvalidId {
+        line,
+        value: raw_id.to_string(),
+    })
+}
+
+fn parse_range(raw_range: &str, line: usize) -> Result<(u64, u64), DatabaseError> {
+    let (begin, end) = raw_range
+        .split_once("-")
+        .ok_or(DatabaseError::MissingSeparator { line })?;
+    let (begin, end) = (parse_id(begin, line)?, parse_id(end, line)?);
+    if begin > end {
+        return Err(DatabaseError::ReversedRange { line, begin, end });
+    }
+    Ok((begin, end))
+}
+
+/// Reads a database line by line: the ranges section up to the first blank line, then
+/// one id at a time, so the id list never has to fit in memory.
+///
+/// Lines may end in either `\n` or `\r\n`.
+struct DatabaseReader<R> {
+    reader: R,
+    line: String,
+    line_no: usize,
+}
+
+impl<R: BufRead> DatabaseReader<R> {
+    fn new(reader: R) -> DatabaseReader<R> {
+        DatabaseReader {
+            reader,
+            line: String::new(),
+            line_no: 0,
+        }
+    }
+
+    // returns the line number alongside the line, without its line ending
+    fn next_line(&mut self) -> Result<Option<(usize, &str)>, DatabaseError> {
+        self.line.clear();
+        self.line_no += 1;
+        let read =
+            self.reader
+                .read_line(&mut self.line)
+                .map_err(|e| DatabaseError::Unreadable {
+                    line: self.line_no,
+                    reason: e.to_string(),
+                })?;
+        if read == 0 {
+            return Ok(None);
+        }
+        Ok(Some((
+            self.line_no,
+            self.line.trim_end_matches(['\r', '\n']),
+        )))
+    }
+
+    fn read_ranges(&mut self) -> Result<RangesContainer, DatabaseError> {
+        let mut ranges = Vec::new();
+        while let Some((line, raw_range)) = self.next_line()? {
+            if raw_range.trim().is_empty() {
+                break;
+            }
+            ranges.push(parse_range(raw_range, line)?);
+        }
+        Ok(RangesContainer::from_ranges(ranges))
+    }
+
+    // blank lines in the id section are skipped
+    fn next_id(&mut self) -> Result<Option<u64>, DatabaseError> {
+        while let Some((line, raw_id)) = self.next_line()? {
+            if !raw_id.trim().is_empty() {
+                return parse_id(raw_id, line).map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Static interval tree over the original ranges, answering "which ranges contain this id".
+///
+/// The ranges are sorted by their first id and the tree is implicit: the root of the slice
+/// `lo..hi` is its middle element, and every node also stores the largest end within its
+/// subtree so whole subtrees ending before the id are skipped.
+struct IntervalTree {
+    // (begin, end, line number)
+    nodes: Vec<(u64, u64, usize)>,
+    max_end: Vec<u64>,
+}
+
+impl IntervalTree {
+    fn new(ranges: &[(u64, u64)]) -> IntervalTree {
+        let mut nodes: Vec<(u64, u64, usize)> = ranges
+            .iter()
+            .enumerate()
+            .map(|(idx, (begin, end))| (*begin, *end, idx + 1))
+            .collect();
+        nodes.sort_unstable();
+        let mut tree = IntervalTree {
+            max_end: vec![0; nodes.len()],
+            nodes,
+        };
+        tree.build(0, tree.nodes.len());
+        tree
+    }
+
+    fn build(&mut self, lo: usize, hi: usize) -> u64 {
+        if lo >= hi {
+            return 0;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let max_end = self.nodes[mid]
+            .1
+            .max(self.build(lo, mid))
+            .max(self.build(mid + 1, hi));
+        self.max_end[mid] = max_end;
+        max_end
+    }
+
+    fn stab(&self, id: u64, lo: usize, hi: usize, lines: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max_end[mid] < id {
+            return;
+        }
+        self.stab(id, lo, mid, lines);
+        let (begin, end, line) = self.nodes[mid];
+        if begin <= id {
+            if id <= end {
+                lines.push(line);
+            }
+            // everything on the right begins after `begin`, possibly still before `id`
+            self.stab(id, mid + 1, hi, lines);
+        }
+    }
+}
 
 struct RangesContainer {
+    // ranges as listed in the database
     ranges: Vec<(u64, u64)>,
+    // sorted, non-overlapping view of `ranges` used to answer queries
+    merged: Vec<(u64, u64)>,
+    // tracks which of `ranges` an id came from
+    sources: IntervalTree,
 }
 
 impl RangesContainer {
-    fn new(raw_ranges: &str) -> RangesContainer {
-        let ranges: Vec<(u64, u64)> = raw_ranges
-            .lines()
-            .map(|r| r.split_once("-").unwrap())
-            .map(|(begin, end)| (begin.parse().unwrap(), end.parse().unwrap()))
-            .collect();
-        RangesContainer { ranges }
+    fn from_ranges(ranges: Vec<(u64, u64)>) -> RangesContainer {
+        let merged = merge_intervals(&ranges);
+        let sources = IntervalTree::new(&ranges);
+        RangesContainer {
+            ranges,
+            merged,
+            sources,
+        }
     }
+
+    /// Line numbers of every range containing `id`, in ascending order.
+    ///
+    /// Ranges are numbered from 1 in the order they were listed, which is their line
+    /// number in the database file.
+    fn matching_lines(&self, id: u64) -> Vec<usize> {
+        let mut lines = Vec::new();
+        self.sources
+            .stab(id, 0, self.sources.nodes.len(), &mut lines);
+        lines.sort_unstable();
+        lines
+    }
+
     fn is_within_range(&self, id: u64) -> bool {
+        let next = self.merged.partition_point(|(begin, _)| *begin <= id);
+        next > 0 && id <= self.merged[next - 1].1
+    }
+
+    // the original O(ranges) scan, kept as a reference for tests and the benchmark
+    fn is_within_range_linear(&self, id: u64) -> bool {
         self.ranges
             .iter()
             .any(|(begin, end)| id >= *begin && id <= *end)
     }
+
+    /// Answers `is_within_range` for every id at once, in the order they were given.
+    ///
+    /// The ids are visited in sorted order so a single forward sweep over the merged
+    /// intervals is enough.
+    fn are_within_range(&self, ids: &[u64]) -> Vec<bool> {
+        let mut order: Vec<usize> = (0..ids.len()).collect();
+        order.sort_unstable_by_key(|idx| ids[*idx]);
+
+        let mut result = vec![false; ids.len()];
+        let mut intervals = self.merged.iter().peekable();
+        for idx in order {
+            let id = ids[idx];
+            while intervals.next_if(|(_, end)| *end < id).is_some() {}
+            result[idx] = intervals.peek().is_some_and(|(begin, _)| *begin <= id);
+        }
+        result
+    }
+
+    fn union(&self, other: &RangesContainer) -> RangesContainer {
+        let mut ranges = self.merged.clone();
+        ranges.extend_from_slice(&other.merged);
+        Self::from_ranges(ranges)
+    }
+
+    fn intersection(&self, other: &RangesContainer) -> RangesContainer {
+        let mut ranges = Vec::new();
+        let (mut left, mut right) = (
+            self.merged.iter().peekable(),
+            other.merged.iter().peekable(),
+        );
+        while let (Some((l_begin, l_end)), Some((r_begin, r_end))) = (left.peek(), right.peek()) {
+            let begin = *l_begin.max(r_begin);
+            let end = *l_end.min(r_end);
+            if begin <= end {
+                ranges.push((begin, end));
+            }
+            // the interval ending first can't overlap anything else on the other side
+            if l_end < r_end {
+                left.next();
+            } else {
+                right.next();
+            }
+        }
+        Self::from_ranges(ranges)
+    }
+
+    /// Ids contained in `self` but not in `other`.
+    fn difference(&self, other: &RangesContainer) -> RangesContainer {
+        let mut ranges = Vec::new();
+        let mut removed = other.merged.iter().peekable();
+        for (begin, end) in &self.merged {
+            while removed.next_if(|(_, r_end)| r_end < begin).is_some() {}
+
+            // first id of this interval not yet known to be kept or removed
+            let mut cursor = Some(*begin);
+            for (r_begin, r_end) in removed.clone().take_while(|(r_begin, _)| r_begin <= end) {
+                let Some(next) = cursor else {
+                    break;
+                };
+                if *r_begin > next {
+                    ranges.push((next, r_begin - 1));
+                }
+                cursor = r_end.checked_add(1);
+            }
+            if let Some(next) = cursor
+                && next <= *end
+            {
+                ranges.push((next, *end));
+            }
+        }
+        Self::from_ranges(ranges)
+    }
+
+    /// Ids within `begin..=end` that are not contained in any range.
+    fn complement(&self, begin: u64, end: u64) -> RangesContainer {
+        Self::from_ranges(vec![(begin, end)]).difference(self)
+    }
+
+    /// The smallest equivalent range list: overlapping and adjacent ranges (like `3-5` and
+    /// `6-8`) are coalesced, sorted by their first id.
+    fn normalize(&self) -> RangesContainer {
+        let mut ranges: Vec<(u64, u64)> = Vec::new();
+        for (begin, end) in &self.merged {
+            match ranges.last_mut() {
+                Some((_, prev_end)) if begin - *prev_end == 1 => *prev_end = *end,
+                _ => ranges.push((*begin, *end)),
+            }
+        }
+        Self::from_ranges(ranges)
+    }
+
+    /// The runs of ids lying strictly between consecutive merged intervals.
+    fn gaps(&self) -> Vec<(u64, u64)> {
+        self.merged
+            .windows(2)
+            .filter(|pair| pair[1].0 - pair[0].1 > 1)
+            .map(|pair| (pair[0].1 + 1, pair[1].0 - 1))
+            .collect()
+    }
+}
+
+// writes the ranges back in the database format, one `a-b` per line
+impl Display for RangesContainer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.ranges
+            .iter()
+            .try_for_each(|(begin, end)| writeln!(f, "{begin}-{end}"))
+    }
 }
 
 fn run_problem_1(ranges: &RangesContainer, ids: &[u64]) -> usize {
-    ids.iter().filter(|id| ranges.is_within_range(**id)).count()
+    ranges
+        .are_within_range(ids)
+        .iter()
+        .filter(|fresh| **fresh)
+        .count()
 }
 
 fn find_overlap(counted: &[(u64, u64)], begin: u64, end: u64) -> Option<usize> {
@@ -29,8 +339,8 @@ fn find_overlap(counted: &[(u64, u64)], begin: u64, end: u64) -> Option<usize> {
         .iter()
         .enumerate()
         .find(|(_, counted)| {
-            let counted_begin = (**counted).0;
-            let counted_end = (**counted).1;
+            let counted_begin = counted.0;
+            let counted_end = counted.1;
             if begin >= counted_begin && begin <= counted_end {
                 return true;
             }
@@ -40,7 +350,7 @@ fn find_overlap(counted: &[(u64, u64)], begin: u64, end: u64) -> Option<usize> {
             if begin < counted_begin && end > counted_end {
                 return true;
             }
-            return false;
+            false
         })
         .map(|v| v.0)
 }
@@ -64,9 +374,8 @@ fn merge_range_inner(ranges: &[(u64, u64)]) -> (Vec<(u64, u64)>, bool) {
     (final_range, merged)
 }
 
-
 // this is my initial impl, there's a better one using a clever Merge Interval algorithm
-fn run_problem_2_brute_force(ranges: &RangesContainer) -> u64 {
+fn run_problem_2_brute_force(ranges: &RangesContainer) -> u128 {
     let mut ranges = ranges.ranges.clone();
 
     loop {
@@ -77,12 +386,14 @@ fn run_problem_2_brute_force(ranges: &RangesContainer) -> u64 {
             break;
         }
     }
-    ranges.iter().map(|(begin, end)| end - begin + 1).sum()
+    ranges
+        .iter()
+        .map(|(begin, end)| range_len(*begin, *end))
+        .sum()
 }
 
-
-fn run_problem_2(ranges: &RangesContainer) -> u64 {
-    let mut ranges = ranges.ranges.clone();
+fn merge_intervals(ranges: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut ranges = ranges.to_vec();
     ranges.sort_by_key(|r| r.0);
 
     let mut merged_interval: Vec<(u64, u64)> = Vec::new();
@@ -99,43 +410,346 @@ fn run_problem_2(ranges: &RangesContainer) -> u64 {
         }
     });
 
-    merged_interval.iter().map(|(begin, end)| end - begin + 1).sum()
+    merged_interval
+}
+
+// `0-18446744073709551615` alone holds 2^64 ids, so counts don't fit in a u64
+fn range_len(begin: u64, end: u64) -> u128 {
+    (end - begin) as u128 + 1
+}
+
+fn run_problem_2(ranges: &RangesContainer) -> u128 {
+    ranges
+        .merged
+        .iter()
+        .map(|(begin, end)| range_len(*begin, *end))
+        .sum()
+}
+
+fn generate_ranges(rng: &mut Rng, count: usize, max_id: u64) -> Vec<(u64, u64)> {
+    let max_len = (max_id / count as u64).max(1);
+    (0..count)
+        .map(|_| {
+            let begin = rng.range(0..=max_id);
+            (
+                begin,
+                begin.saturating_add(rng.range(0..=max_len)).min(max_id),
+            )
+        })
+        .collect()
+}
+
+/// Ranges shaped to hit the merge edge cases: overlapping, nested, touching, duplicated
+/// and single-point ranges over a small id space.
+fn generate_edge_case_ranges(rng: &mut Rng, count: usize, max_id: u64) -> Vec<(u64, u64)> {
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    for _ in 0..count {
+        let random = |rng: &mut Rng| {
+            let begin = rng.range(0..=max_id);
+            (begin, rng.range(begin..=max_id))
+        };
+        let range = match (ranges.is_empty(), rng.range(0..=5)) {
+            (true, _) | (_, 0) => random(rng),
+            (false, shape) => {
+                let (begin, end) = ranges[rng.index(ranges.len())];
+                match shape {
+                    // nested
+                    1 => {
+                        let inner_begin = rng.range(begin..=end);
+                        (inner_begin, rng.range(inner_begin..=end))
+                    }
+                    // touching, either sharing the end point or right after it
+                    2 => {
+                        let touch = (end + rng.range(0..=1)).min(max_id);
+                        (touch, rng.range(touch..=max_id))
+                    }
+                    // overlapping the start
+                    3 => (rng.range(0..=begin), rng.range(begin..=max_id)),
+                    4 => (begin, end),
+                    _ => {
+                        let point = rng.range(0..=max_id);
+                        (point, point)
+                    }
+                }
+            }
+        };
+        ranges.push(range);
+    }
+    ranges
+}
+
+fn implementations_agree(ranges: &[(u64, u64)]) -> bool {
+    let container = RangesContainer::from_ranges(ranges.to_vec());
+    run_problem_2(&container) == run_problem_2_brute_force(&container)
 }
 
+/// Greedily shrinks a failing range list while `fails` keeps returning true: by dropping
+/// whole ranges, by shifting or halving every bound at once (which keeps ranges that
+/// touch still touching), and by pulling the bounds of single ranges towards zero.
+fn shrink_ranges(
+    mut ranges: Vec<(u64, u64)>,
+    fails: impl Fn(&[(u64, u64)]) -> bool,
+) -> Vec<(u64, u64)> {
+    let mut progress = true;
+    while progress {
+        progress = false;
+
+        let lowest = ranges.iter().map(|(begin, _)| *begin).min().unwrap_or(0);
+        let shifted: Vec<(u64, u64)> = ranges
+            .iter()
+            .map(|(begin, end)| (begin - lowest, end - lowest))
+            .collect();
+        let halved: Vec<(u64, u64)> = ranges
+            .iter()
+            .map(|(begin, end)| (begin / 2, end / 2))
+            .collect();
+        for candidate in [shifted, halved] {
+            if candidate != ranges && fails(&candidate) {
+                ranges = candidate;
+                progress = true;
+            }
+        }
+
+        let mut idx = 0;
+        while idx < ranges.len() {
+            let mut candidate = ranges.clone();
+            candidate.remove(idx);
+            if fails(&candidate) {
+                ranges = candidate;
+                progress = true;
+            } else {
+                idx += 1;
+            }
+        }
+
+        for idx in 0..ranges.len() {
+            let (begin, end) = ranges[idx];
+            let smaller = [
+                (begin, begin),
+                (0, end - begin),
+                (begin / 2, end - (begin - begin / 2)),
+                (begin, begin + (end - begin) / 2),
+            ];
+            for candidate_range in smaller {
+                if candidate_range == ranges[idx] {
+                    continue;
+                }
+                let mut candidate = ranges.clone();
+                candidate[idx] = candidate_range;
+                if fails(&candidate) {
+                    ranges = candidate;
+                    progress = true;
+                    break;
+                }
+            }
+        }
+    }
+    ranges
+}
+
+/// Runs `agree` on `cases` generated range lists and returns the shrunk counterexample
+/// for the first one it rejects.
+fn differential_test(
+    rng: &mut Rng,
+    cases: usize,
+    agree: impl Fn(&[(u64, u64)]) -> bool,
+) -> Result<(), Vec<(u64, u64)>> {
+    for case in 0..cases {
+        let max_id = if case % 2 == 0 { 50 } else { 1_000_000 };
+        let count = rng.range(1..=30) as usize;
+        let ranges = generate_edge_case_ranges(rng, count, max_id);
+        if !agree(&ranges) {
+            return Err(shrink_ranges(ranges, |r| !agree(r)));
+        }
+    }
+    Ok(())
+}
+
+fn run_benchmark(range_count: usize, id_count: usize) {
+    let mut rng = Rng::new(5);
+    let max_id = 1_000_000_000_000;
+    let ranges = RangesContainer::from_ranges(generate_ranges(&mut rng, range_count, max_id));
+    let ids: Vec<u64> = (0..id_count).map(|_| rng.range(0..=max_id)).collect();
+    println!(
+        "{range_count} ranges ({} after merging), {id_count} ids",
+        ranges.merged.len()
+    );
+
+    let timed = |name: &str, query: &dyn Fn() -> usize| {
+        let start = Instant::now();
+        let fresh = query();
+        println!("{name:>14}: {fresh} fresh in {:?}", start.elapsed());
+    };
+    timed("linear scan", &|| {
+        ids.iter()
+            .filter(|id| ranges.is_within_range_linear(**id))
+            .count()
+    });
+    timed("binary search", &|| {
+        ids.iter().filter(|id| ranges.is_within_range(**id)).count()
+    });
+    timed("sorted sweep", &|| run_problem_1(&ranges, &ids));
+}
 
 fn main() {
-    let filename = "day5.txt";
-    let (ranges, ids) = parse_input(filename);
-    println!("problem 1: {}", run_problem_1(&ranges, &ids));
-    println!("problem 2: {}", run_problem_2(&ranges));
-    println!("problem 2 (brute force): {}", run_problem_2_brute_force(&ranges));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let number = |idx: usize, default: usize| {
+        args.get(idx)
+            .map_or(default, |c| c.parse().expect("expected a number"))
+    };
+
+    match args.first().map(String::as_str) {
+        Some("bench") => run_benchmark(number(1, 10_000), number(2, 100_000)),
+        Some("difftest") => {
+            let mut rng = Rng::new(number(2, 0) as u64);
+            match differential_test(&mut rng, number(1, 10_000), implementations_agree) {
+                Ok(()) => println!("run_problem_2 and run_problem_2_brute_force agree"),
+                Err(ranges) => {
+                    println!("implementations disagree on {ranges:?}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(op @ ("union" | "intersection" | "difference")) => {
+            let (Some(a), Some(b)) = (args.get(1), args.get(2)) else {
+                panic!("usage: day5 {op} FILE_A FILE_B");
+            };
+            let (a, b) = (or_exit(a, parse_ranges(a)), or_exit(b, parse_ranges(b)));
+            let result = match op {
+                "union" => a.union(&b),
+                "intersection" => a.intersection(&b),
+                _ => a.difference(&b),
+            };
+            print!("{}", result.normalize());
+        }
+        Some("spoiled") => {
+            let (Some(file), Some(begin), Some(end)) = (args.get(1), args.get(2), args.get(3))
+            else {
+                panic!("usage: day5 spoiled FILE BEGIN END");
+            };
+            let bound = |b: &String| -> u64 { b.parse().expect("bounds must be ids") };
+            let ranges = or_exit(file, parse_ranges(file));
+            print!(
+                "{}",
+                ranges.complement(bound(begin), bound(end)).normalize()
+            );
+        }
+        Some("stream") => {
+            // "-" streams the database from stdin instead of an embedded asset
+            let file = args.get(1).map_or("day5.txt", String::as_str);
+            let (ranges, fresh) = if file == "-" {
+                or_exit(file, count_fresh_streaming(std::io::stdin().lock()))
+            } else {
+                or_exit(
+                    file,
+                    count_fresh_streaming(Cursor::new(open_assets(file).unwrap().data)),
+                )
+            };
+            println!("problem 1: {fresh}");
+            println!("problem 2: {}", run_problem_2(&ranges));
+        }
+        Some("audit") => {
+            let file = args.get(1).map_or("day5.txt", String::as_str);
+            let (ranges, ids) = or_exit(file, parse_input(file));
+            print!("{}", audit_report(&ranges, &ids));
+        }
+        Some("normalize") => {
+            let file = args.get(1).map_or("day5.txt", String::as_str);
+            print!("{}", or_exit(file, parse_ranges(file)).normalize());
+        }
+        Some("gaps") => {
+            let file = args.get(1).map_or("day5.txt", String::as_str);
+            let gaps = or_exit(file, parse_ranges(file)).gaps();
+            print!("{}", RangesContainer::from_ranges(gaps.clone()));
+            if let Some((begin, end)) = gaps.iter().max_by_key(|(begin, end)| end - begin) {
+                println!(
+                    "largest gap: {begin}-{end} ({} ids)",
+                    range_len(*begin, *end)
+                );
+            }
+        }
+        _ => {
+            let filename = args.first().map_or("day5.txt", String::as_str);
+            let (ranges, ids) = or_exit(filename, parse_input(filename));
+            println!("problem 1: {}", run_problem_1(&ranges, &ids));
+            println!("problem 2: {}", run_problem_2(&ranges));
+        }
+    }
+}
+
+/// Lists every fresh id with the lines that made it fresh, followed by the spoiled ids.
+fn audit_report(ranges: &RangesContainer, ids: &[u64]) -> String {
+    let mut report = String::new();
+    let mut spoiled = Vec::new();
+    for id in ids {
+        let lines = ranges.matching_lines(*id);
+        if lines.is_empty() {
+            spoiled.push(id.to_string());
+        } else {
+            let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+            report += &format!("{id}: fresh (lines {})\n", lines.join(", "));
+        }
+    }
+    report += &format!("{} spoiled: {}\n", spoiled.len(), spoiled.join(", "));
+    report
 }
 
-fn parse_input(file_name: &str) -> (RangesContainer, Vec<u64>) {
-    let raw_data = read_to_string(file_name).unwrap();
-    let data = raw_data.split_once("\n\n").unwrap();
+fn or_exit<T>(file_name: &str, parsed: Result<T, DatabaseError>) -> T {
+    parsed.unwrap_or_else(|e| {
+        eprintln!("{file_name}: {e}");
+        std::process::exit(1);
+    })
+}
 
-    let ranges = RangesContainer::new(data.0);
-    let ids = data
-        .1
-        .lines()
-        .map(|raw_id| raw_id.parse().unwrap())
-        .collect();
+fn open_database(file_name: &str) -> DatabaseReader<Cursor<Cow<'static, [u8]>>> {
+    DatabaseReader::new(Cursor::new(open_assets(file_name).unwrap().data))
+}
 
-    (ranges, ids)
+// accepts both a full database and a file holding only the ranges section
+fn parse_ranges(file_name: &str) -> Result<RangesContainer, DatabaseError> {
+    open_database(file_name).read_ranges()
+}
+
+fn parse_input(file_name: &str) -> Result<(RangesContainer, Vec<u64>), DatabaseError> {
+    let mut reader = open_database(file_name);
+    let ranges = reader.read_ranges()?;
+    let mut ids = Vec::new();
+    while let Some(id) = reader.next_id()? {
+        ids.push(id);
+    }
+    Ok((ranges, ids))
+}
+
+/// Problem 1 without collecting the ids: each one is checked as soon as it's read.
+fn count_fresh_streaming<R: BufRead>(reader: R) -> Result<(RangesContainer, usize), DatabaseError> {
+    let mut reader = DatabaseReader::new(reader);
+    let ranges = reader.read_ranges()?;
+    let mut fresh = 0;
+    while let Some(id) = reader.next_id()? {
+        if ranges.is_within_range(id) {
+            fresh += 1;
+        }
+    }
+    Ok((ranges, fresh))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    impl RangesContainer {
+        fn new(raw_ranges: &str) -> Result<RangesContainer, DatabaseError> {
+            DatabaseReader::new(raw_ranges.as_bytes()).read_ranges()
+        }
+    }
+
     #[test]
     fn id_tests() {
         let src = "3-5
 10-14
 16-20
 12-18";
-        let ranges = RangesContainer::new(src);
+        let ranges = RangesContainer::new(src).unwrap();
         assert!(ranges.is_within_range(3));
         assert!(ranges.is_within_range(5));
         assert!(ranges.is_within_range(11));
@@ -145,6 +759,22 @@ mod tests {
         assert!(!ranges.is_within_range(32));
     }
 
+    #[test]
+    fn batched_query_tests() {
+        let mut rng = Rng::new(29);
+        for _ in 0..100 {
+            let ranges = RangesContainer::from_ranges(generate_ranges(&mut rng, 20, 1000));
+            let ids: Vec<u64> = (0..200).map(|_| rng.range(0..=1000)).collect();
+            let expected: Vec<bool> = ids
+                .iter()
+                .map(|id| ranges.is_within_range_linear(*id))
+                .collect();
+            let single: Vec<bool> = ids.iter().map(|id| ranges.is_within_range(*id)).collect();
+            assert_eq!(single, expected);
+            assert_eq!(ranges.are_within_range(&ids), expected);
+        }
+    }
+
     #[test]
     fn sum_tests_1() {
         let src = "3-5
@@ -152,7 +782,221 @@ mod tests {
 10-14
 16-20
 12-18";
-        let ranges = RangesContainer::new(src);
+        let ranges = RangesContainer::new(src).unwrap();
+        assert_eq!(run_problem_2(&ranges), 14);
+    }
+
+    #[test]
+    fn merge_implementations_agree() {
+        let mut rng = Rng::new(30);
+        assert_eq!(
+            differential_test(&mut rng, 5000, implementations_agree),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn differential_test_shrinks_failures() {
+        // a merge that forgets about touching-at-the-end overlaps
+        let broken = |ranges: &[(u64, u64)]| {
+            let mut ranges = ranges.to_vec();
+            ranges.sort();
+            let mut merged: Vec<(u64, u64)> = Vec::new();
+            for (begin, end) in ranges {
+                match merged.last_mut() {
+                    Some((_, prev_end)) if begin < *prev_end => *prev_end = end.max(*prev_end),
+                    _ => merged.push((begin, end)),
+                }
+            }
+            merged
+                .iter()
+                .map(|(begin, end)| range_len(*begin, *end))
+                .sum::<u128>()
+        };
+        let agree = |ranges: &[(u64, u64)]| {
+            broken(ranges) == run_problem_2(&RangesContainer::from_ranges(ranges.to_vec()))
+        };
+
+        let counterexample = differential_test(&mut Rng::new(30), 5000, agree).unwrap_err();
+        assert_eq!(counterexample, vec![(0, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn set_operation_tests() {
+        let mut rng = Rng::new(31);
+        for _ in 0..300 {
+            let a = RangesContainer::from_ranges(generate_edge_case_ranges(&mut rng, 8, 60));
+            let b = RangesContainer::from_ranges(generate_edge_case_ranges(&mut rng, 8, 60));
+            let union = a.union(&b);
+            let intersection = a.intersection(&b);
+            let difference = a.difference(&b);
+            let complement = a.complement(10, 50);
+            for id in 0..=62 {
+                let (in_a, in_b) = (a.is_within_range(id), b.is_within_range(id));
+                assert_eq!(union.is_within_range(id), in_a || in_b);
+                assert_eq!(intersection.is_within_range(id), in_a && in_b);
+                assert_eq!(difference.is_within_range(id), in_a && !in_b);
+                assert_eq!(
+                    complement.is_within_range(id),
+                    !in_a && (10..=50).contains(&id)
+                );
+            }
+        }
+
+        let everything = RangesContainer::from_ranges(vec![(0, u64::MAX)]);
+        let edges = RangesContainer::from_ranges(vec![(0, 0), (u64::MAX, u64::MAX)]);
+        assert_eq!(
+            everything.difference(&edges).merged,
+            vec![(1, u64::MAX - 1)]
+        );
+        assert_eq!(
+            edges.complement(0, u64::MAX).merged,
+            vec![(1, u64::MAX - 1)]
+        );
+    }
+
+    #[test]
+    fn gaps_tests() {
+        let ranges = RangesContainer::new("3-5\n10-14\n16-20\n12-18\n21-21").unwrap();
+        assert_eq!(ranges.gaps(), vec![(6, 9)]);
+        let ranges = RangesContainer::new("3-5\n7-7\n10-14").unwrap();
+        assert_eq!(ranges.gaps(), vec![(6, 6), (8, 9)]);
+    }
+
+    #[test]
+    fn extreme_totals() {
+        let everything = RangesContainer::new("0-18446744073709551615").unwrap();
+        assert_eq!(run_problem_2(&everything), 1 << 64);
+        assert_eq!(run_problem_2_brute_force(&everything), 1 << 64);
+        assert!(everything.is_within_range(u64::MAX));
+
+        let edges = RangesContainer::new(
+            "18446744073709551615-18446744073709551615\n0-0\n18446744073709551614-18446744073709551615",
+        )
+        .unwrap();
+        assert_eq!(run_problem_2(&edges), 3);
+        assert_eq!(run_problem_2_brute_force(&edges), 3);
+        assert!(!edges.is_within_range(u64::MAX - 2));
+
+        // each range alone is close to u64::MAX, together they overflow it
+        let halves =
+            RangesContainer::new("0-9223372036854775807\n9223372036854775808-18446744073709551614")
+                .unwrap();
+        assert_eq!(run_problem_2(&halves), u64::MAX as u128);
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        assert_eq!(
+            RangesContainer::new("3-5\n22-11").err(),
+            Some(DatabaseError::ReversedRange {
+                line: 2,
+                begin: 22,
+                end: 11
+            })
+        );
+        assert_eq!(
+            RangesContainer::new("3-5\n10-14\n12").err(),
+            Some(DatabaseError::MissingSeparator { line: 3 })
+        );
+        assert_eq!(
+            RangesContainer::new("3-18446744073709551616").err(),
+            Some(DatabaseError::InvalidId {
+                line: 1,
+                value: "18446744073709551616".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn normalize_tests() {
+        let ranges = RangesContainer::new("6-8\n3-5\n12-18\n10-11\n20-20\n14-14").unwrap();
+        let normalized = ranges.normalize();
+        assert_eq!(normalized.ranges, vec![(3, 8), (10, 18), (20, 20)]);
+        assert_eq!(normalized.to_string(), "3-8\n10-18\n20-20\n");
+
+        let extremes = RangesContainer::new("0-5\n6-18446744073709551615").unwrap();
+        assert_eq!(extremes.normalize().ranges, vec![(0, u64::MAX)]);
+
+        let mut rng = Rng::new(33);
+        for _ in 0..300 {
+            let ranges = RangesContainer::from_ranges(generate_edge_case_ranges(&mut rng, 10, 60));
+            let normalized = ranges.normalize();
+            let reparsed = RangesContainer::new(&normalized.to_string()).unwrap();
+            assert_eq!(reparsed.ranges, normalized.ranges);
+            assert_eq!(run_problem_2(&reparsed), run_problem_2(&ranges));
+            assert!(
+                normalized
+                    .ranges
+                    .windows(2)
+                    .all(|pair| pair[1].0 > pair[0].1 + 1)
+            );
+            for id in 0..=62 {
+                assert_eq!(reparsed.is_within_range(id), ranges.is_within_range(id));
+            }
+        }
+    }
+
+    #[test]
+    fn matching_lines_tests() {
+        let (ranges, ids) = parse_input("day5_example.txt").unwrap();
+        assert_eq!(ranges.matching_lines(5), vec![1]);
+        assert_eq!(ranges.matching_lines(17), vec![3, 4]);
+        assert_eq!(ranges.matching_lines(8), Vec::<usize>::new());
+        assert_eq!(
+            audit_report(&ranges, &ids),
+            "5: fresh (lines 1)\n11: fresh (lines 2)\n17: fresh (lines 3, 4)\n3 spoiled: 1, 8, 32\n"
+        );
+
+        let mut rng = Rng::new(34);
+        for _ in 0..200 {
+            let ranges = RangesContainer::from_ranges(generate_edge_case_ranges(&mut rng, 25, 60));
+            for id in 0..=62 {
+                let expected: Vec<usize> = ranges
+                    .ranges
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (begin, end))| (*begin..=*end).contains(&id))
+                    .map(|(idx, _)| idx + 1)
+                    .collect();
+                assert_eq!(ranges.matching_lines(id), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_tests() {
+        let example =
+            String::from_utf8(open_assets("day5_example.txt").unwrap().data.to_vec()).unwrap();
+        let (ranges, fresh) = count_fresh_streaming(example.as_bytes()).unwrap();
+        assert_eq!(fresh, 3);
         assert_eq!(run_problem_2(&ranges), 14);
+
+        let crlf = example.replace('\n', "\r\n");
+        let (ranges, fresh) = count_fresh_streaming(crlf.as_bytes()).unwrap();
+        assert_eq!(fresh, 3);
+        assert_eq!(run_problem_2(&ranges), 14);
+
+        let (_, fresh) = count_fresh_streaming("3-5\r\n\r\n4\r\n\r\n5\r\n6".as_bytes()).unwrap();
+        assert_eq!(fresh, 2);
+        assert_eq!(
+            count_fresh_streaming("3-5\r\n\r\n4\r\nfive\r\n".as_bytes()).err(),
+            Some(DatabaseError::InvalidId {
+                line: 4,
+                value: "five".to_string()
+            })
+        );
+        assert_eq!(
+            count_fresh_streaming("3-5\r\n5-4\r\n".as_bytes()).err(),
+            Some(DatabaseError::ReversedRange {
+                line: 2,
+                begin: 5,
+                end: 4
+            })
+        );
+        assert!(matches!(
+            count_fresh_streaming(&[b'1', b'-', 0xff, b'\n'][..]),
+            Err(DatabaseError::Unreadable { line: 1, .. })
+        ));
     }
 }
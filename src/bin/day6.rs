@@ -1,53 +1,413 @@
 use aoc_2025::assets::read_to_string;
+use aoc_2025::rng::Rng;
+use std::fmt::{Display, Formatter};
 
-#[derive(Debug)]
+const DEFAULT_TAB_WIDTH: usize = 8;
+
+#[derive(Debug, Clone, Copy)]
+struct ParseOptions {
+    // tabs are expanded to the next multiple of this width before columns are compared
+    tab_width: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            tab_width: DEFAULT_TAB_WIDTH,
+        }
+    }
+}
+
+// lines and columns are 1-based, columns are counted after tab expansion
+#[derive(Debug, PartialEq)]
+enum SheetError {
+    EmptySheet,
+    MissingOperatorRow,
+    MultipleOperatorRows {
+        first_line: usize,
+        second_line: usize,
+    },
+    UnexpectedCharacter {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    UnknownOperator {
+        line: usize,
+        column: usize,
+        symbol: char,
+    },
+    MissingOperator {
+        line: usize,
+        column: usize,
+    },
+    MultipleOperators {
+        line: usize,
+        column: usize,
+    },
+    MissingOperands {
+        column: usize,
+    },
+}
+
+impl Display for SheetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SheetError::EmptySheet => write!(f, "worksheet is empty"),
+            SheetError::MissingOperatorRow => write!(f, "no operator row found"),
+            SheetError::MultipleOperatorRows {
+                first_line,
+                second_line,
+            } => write!(
+                f,
+                "lines {first_line} and {second_line} both look like operator rows"
+            ),
+            SheetError::UnexpectedCharacter {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {line}, column {column}: unexpected character {found:?}"
+            ),
+            SheetError::UnknownOperator {
+                line,
+                column,
+                symbol,
+            } => write!(
+                f,
+                "line {line}, column {column}: unknown operator {symbol:?}"
+            ),
+            SheetError::MissingOperator { line, column } => write!(
+                f,
+                "line {line}, column {column}: problem starting here has no operator"
+            ),
+            SheetError::MultipleOperators { line, column } => write!(
+                f,
+                "line {line}, column {column}: second operator in the same problem"
+            ),
+            SheetError::MissingOperands { column } => {
+                write!(f, "column {column}: problem starting here has no operands")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
 struct ProblemSheet {
     problems: Vec<Problem>,
 }
 
+fn expand_line(line: &str, tab_width: usize) -> Vec<char> {
+    let tab_width = tab_width.max(1);
+    let mut expanded = Vec::new();
+    for c in line.chars() {
+        if c == '\t' {
+            let padding = tab_width - expanded.len() % tab_width;
+            expanded.extend(std::iter::repeat_n(' ', padding));
+        } else {
+            expanded.push(c);
+        }
+    }
+
+    // `lines()` already drops "\r\n", this also covers a stray '\r' and trailing blanks
+    while expanded.last().is_some_and(|c| c.is_whitespace()) {
+        expanded.pop();
+    }
+    expanded
+}
+
+struct SheetLine {
+    line_no: usize,
+    chars: Vec<char>,
+}
+
+impl SheetLine {
+    fn char_at(&self, col: usize) -> char {
+        self.chars.get(col).copied().unwrap_or(' ')
+    }
+
+    fn is_blank_at(&self, col: usize) -> bool {
+        self.char_at(col) == ' '
+    }
+}
+
 impl ProblemSheet {
-    fn new(raw_problem: &str) -> Self {
-        let problem_lines: Vec<Vec<char>> =
-            raw_problem.lines().map(|s| s.chars().collect()).collect();
+    fn new(raw_problem: &str, options: &ParseOptions) -> Result<Self, SheetError> {
+        let lines: Vec<SheetLine> = raw_problem
+            .lines()
+            .enumerate()
+            .map(|(idx, l)| SheetLine {
+                line_no: idx + 1,
+                chars: expand_line(l, options.tab_width),
+            })
+            .filter(|l| !l.chars.is_empty())
+            .collect();
 
-        let max_len = problem_lines.iter().map(|l| l.len()).max().unwrap();
-        let operands_each_problem = problem_lines.len() - 1;
+        if lines.is_empty() {
+            return Err(SheetError::EmptySheet);
+        }
+
+        // the operator row is the only row without any digit in it
+        let mut operator_row_idx = None;
+        for (idx, line) in lines.iter().enumerate() {
+            if line.chars.iter().any(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            if let Some(first) = operator_row_idx {
+                let first: &SheetLine = &lines[first];
+                return Err(SheetError::MultipleOperatorRows {
+                    first_line: first.line_no,
+                    second_line: line.line_no,
+                });
+            }
+            operator_row_idx = Some(idx);
+        }
+        let operator_row_idx = operator_row_idx.ok_or(SheetError::MissingOperatorRow)?;
+        let operator_row = &lines[operator_row_idx];
+        let operand_rows: Vec<&SheetLine> = lines
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != operator_row_idx)
+            .map(|(_, l)| l)
+            .collect();
 
+        for (col, c) in operator_row.chars.iter().enumerate() {
+            if *c != ' ' && Operator::new(*c).is_none() {
+                return Err(SheetError::UnknownOperator {
+                    line: operator_row.line_no,
+                    column: col + 1,
+                    symbol: *c,
+                });
+            }
+        }
+        for row in &operand_rows {
+            for (col, c) in row.chars.iter().enumerate() {
+                if *c != ' ' && !c.is_ascii_digit() {
+                    return Err(SheetError::UnexpectedCharacter {
+                        line: row.line_no,
+                        column: col + 1,
+                        found: *c,
+                    });
+                }
+            }
+        }
+
+        let max_len = lines.iter().map(|l| l.chars.len()).max().unwrap();
         let mut problems: Vec<Problem> = Vec::new();
+        let mut problem_begin = None;
+        for i in 0..=max_len {
+            let is_separator = i == max_len || lines.iter().all(|l| l.is_blank_at(i));
+            match (problem_begin, is_separator) {
+                (None, false) => problem_begin = Some(i),
+                (Some(begin), true) => {
+                    problems.push(Self::parse_problem(operator_row, &operand_rows, begin, i)?);
+                    problem_begin = None;
+                }
+                _ => {}
+            }
+        }
 
-        let mut operands: Vec<Operand> = vec![Operand::new(); operands_each_problem];
-        let mut operator: char = ' ';
-        for i in 0..max_len {
-            let chars_in_each_line: Vec<char> = problem_lines
-                .iter()
-                .map(|l| if i < l.len() { l[i] } else { ' ' })
-                .collect();
-
-            if chars_in_each_line.iter().all(|c| *c == ' ') {
-                let prev_problem = Problem::new(operator, operands);
-                operands = vec![Operand::new(); operands_each_problem];
-                operator = ' ';
-                problems.push(prev_problem);
+        Ok(ProblemSheet { problems })
+    }
+
+    fn parse_problem(
+        operator_row: &SheetLine,
+        operand_rows: &[&SheetLine],
+        begin: usize,
+        end: usize,
+    ) -> Result<Problem, SheetError> {
+        let mut operator = None;
+        for col in begin..end {
+            if operator_row.is_blank_at(col) {
                 continue;
             }
-
-            let possible_operator = *chars_in_each_line.last().unwrap();
-            if possible_operator == '+' || possible_operator == '*' {
-                operator = possible_operator;
+            if operator.is_some() {
+                return Err(SheetError::MultipleOperators {
+                    line: operator_row.line_no,
+                    column: col + 1,
+                });
             }
-            chars_in_each_line
+            operator = Operator::new(operator_row.char_at(col));
+        }
+        let operator = operator.ok_or(SheetError::MissingOperator {
+            line: operator_row.line_no,
+            column: begin + 1,
+        })?;
+
+        // rows that are blank across the whole problem simply don't contribute an operand
+        let operands: Vec<Operand> = operand_rows
+            .iter()
+            .filter(|row| (begin..end).any(|col| !row.is_blank_at(col)))
+            .map(|row| {
+                let mut operand = Operand::new();
+                (begin..end).for_each(|col| operand.add_char(row.char_at(col)));
+                operand
+            })
+            .collect();
+        if operands.is_empty() {
+            return Err(SheetError::MissingOperands { column: begin + 1 });
+        }
+
+        Ok(Problem::new(operator, operands))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Alignment {
+    // write operands exactly as they were parsed
+    Keep,
+    Left,
+    Right,
+}
+
+impl ProblemSheet {
+    /// Writes the sheet back in the puzzle layout: operands top-aligned, operator row last
+    /// with each operator under the first column of its problem, one blank column between
+    /// problems and no trailing whitespace.
+    fn render(&self, alignment: Alignment) -> String {
+        let blocks: Vec<Vec<Vec<char>>> = self
+            .problems
+            .iter()
+            .map(|problem| {
+                let width = match alignment {
+                    Alignment::Keep => problem.operands[0].raw_operand.len(),
+                    _ => problem
+                        .operands
+                        .iter()
+                        .map(|o| o.digits().len())
+                        .max()
+                        .unwrap(),
+                };
+                problem
+                    .operands
+                    .iter()
+                    .map(|o| o.aligned(alignment, width))
+                    .collect()
+            })
+            .collect();
+        let rows = blocks.iter().map(|b| b.len()).max().unwrap_or(0);
+
+        let mut lines: Vec<String> = (0..rows)
+            .map(|row| {
+                blocks
+                    .iter()
+                    .map(|block| match block.get(row) {
+                        Some(operand) => operand.iter().collect(),
+                        None => " ".repeat(block[0].len()),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .collect();
+        lines.push(
+            self.problems
                 .iter()
-                .enumerate()
-                .filter(|(idx, _)| *idx != operands_each_problem)
-                .for_each(|(idx, c)| {
-                    operands[idx].add_char(*c);
+                .zip(&blocks)
+                .map(|(problem, block)| {
+                    format!("{:<1$}", problem.operator.symbol(), block[0].len())
                 })
+                .collect::<Vec<String>>()
+                .join(" "),
+        );
+
+        lines
+            .iter()
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GeneratorConfig {
+    problems: usize,
+    max_operands: usize,
+    max_digits: usize,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            problems: 50,
+            max_operands: 4,
+            max_digits: 4,
         }
+    }
+}
+
+#[derive(Debug)]
+struct GeneratedWorksheet {
+    sheet: ProblemSheet,
+    // expected sums when read row by row (problem 1) and column by column (problem 2)
+    row_answer: u64,
+    column_answer: u64,
+}
+
+fn evaluate_numbers(operator: &Operator, numbers: &[u64]) -> u64 {
+    match operator {
+        Operator::Plus => Problem::plus(numbers),
+        Operator::Multiply => Problem::multiply(numbers),
+    }
+}
+
+fn generate_worksheet(rng: &mut Rng, config: &GeneratorConfig) -> GeneratedWorksheet {
+    // keeps every product below 10^16 so the sums can't overflow a u64
+    assert!(config.max_operands * config.max_digits <= 16 && config.problems <= 100);
+
+    let mut problems = Vec::new();
+    let mut row_answer = 0;
+    let mut column_answer = 0;
+    for _ in 0..config.problems {
+        let operator = if rng.chance(1, 2) {
+            Operator::Plus
+        } else {
+            Operator::Multiply
+        };
+        let alignment = if rng.chance(1, 2) {
+            Alignment::Left
+        } else {
+            Alignment::Right
+        };
+
+        let operand_count = rng.range(1..=config.max_operands as u64) as usize;
+        let numbers: Vec<u64> = (0..operand_count)
+            .map(|_| {
+                let digits = rng.range(1..=config.max_digits as u64) as u32;
+                rng.range(10u64.pow(digits - 1)..=10u64.pow(digits) - 1)
+            })
+            .collect();
+        let width = numbers.iter().map(|n| n.to_string().len()).max().unwrap();
+        let operands: Vec<Operand> = numbers
+            .iter()
+            .map(|n| {
+                let mut operand = Operand::new();
+                n.to_string().chars().for_each(|c| operand.add_char(c));
+                Operand {
+                    raw_operand: operand.aligned(alignment, width),
+                }
+            })
+            .collect();
+
+        let column_numbers: Vec<u64> = (0..width)
+            .map(|col| {
+                let digits: String = operands
+                    .iter()
+                    .map(|o| o.raw_operand[col])
+                    .filter(|c| *c != ' ')
+                    .collect();
+                digits.parse().unwrap()
+            })
+            .collect();
 
-        let last_problem = Problem::new(operator, operands);
-        problems.push(last_problem);
+        row_answer += evaluate_numbers(&operator, &numbers);
+        column_answer += evaluate_numbers(&operator, &column_numbers);
+        problems.push(Problem::new(operator, operands));
+    }
 
-        ProblemSheet { problems }
+    GeneratedWorksheet {
+        sheet: ProblemSheet { problems },
+        row_answer,
+        column_answer,
     }
 }
 
@@ -58,17 +418,24 @@ enum Operator {
 }
 
 impl Operator {
-    fn new(symbol: char) -> Operator {
+    fn new(symbol: char) -> Option<Operator> {
         match symbol {
-            '+' => Operator::Plus,
-            '*' => Operator::Multiply,
-            other => panic!("unknown operator: {other}"),
+            '+' => Some(Operator::Plus),
+            '*' => Some(Operator::Multiply),
+            _ => None,
+        }
+    }
+
+    fn symbol(&self) -> char {
+        match self {
+            Operator::Plus => '+',
+            Operator::Multiply => '*',
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-struct Operand {
+pub struct Operand {
     raw_operand: Vec<char>,
 }
 
@@ -82,6 +449,20 @@ impl Operand {
     fn add_char(&mut self, c: char) {
         self.raw_operand.push(c);
     }
+
+    fn digits(&self) -> String {
+        self.raw_operand.iter().filter(|c| **c != ' ').collect()
+    }
+
+    fn aligned(&self, alignment: Alignment, width: usize) -> Vec<char> {
+        let digits = self.digits();
+        let formatted = match alignment {
+            Alignment::Keep => return self.raw_operand.clone(),
+            Alignment::Left => format!("{digits:<width$}"),
+            Alignment::Right => format!("{digits:>width$}"),
+        };
+        formatted.chars().collect()
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -91,18 +472,15 @@ struct Problem {
 }
 
 impl Problem {
-    fn new(operator: char, operands: Vec<Operand>) -> Self {
-        Problem {
-            operator: Operator::new(operator),
-            operands,
-        }
+    fn new(operator: Operator, operands: Vec<Operand>) -> Self {
+        Problem { operator, operands }
     }
 
-    fn evaluate<T>(&self, transformer: &mut T) -> u64
+    fn evaluate<R>(&self, reading_order: &R) -> u64
     where
-        T: FnMut(&Vec<Operand>) -> Vec<u64>,
+        R: ReadingOrder + ?Sized,
     {
-        let operands = transformer(&self.operands);
+        let operands = reading_order.read_operands(&self.operands);
 
         match self.operator {
             Operator::Plus => Self::plus(&operands),
@@ -110,64 +488,220 @@ impl Problem {
         }
     }
 
-    fn multiply(operands: &Vec<u64>) -> u64 {
-        operands.iter().fold(1, |acc, v| acc * v)
+    fn multiply(operands: &[u64]) -> u64 {
+        operands.iter().product()
     }
 
-    fn plus(operands: &Vec<u64>) -> u64 {
-        operands.iter().fold(0, |acc, v| acc + v)
+    fn plus(operands: &[u64]) -> u64 {
+        operands.iter().sum()
     }
 }
 
-fn sum_problems<T>(problem_sheet: &ProblemSheet, mut transformer: T) -> u64
-where
-    T: Fn(&Vec<Operand>) -> Vec<u64>,
-{
+/// Turns the raw, column-aligned operands of one problem into the numbers to evaluate.
+///
+/// Every operand of a problem has the same width, blanks included, so an implementation
+/// can read the block either row by row or column by column.
+pub trait ReadingOrder {
+    fn name(&self) -> &'static str;
+
+    fn read_operands(&self, operands: &[Operand]) -> Vec<u64>;
+}
+
+/// Each operand row is a number, read left to right (problem 1).
+pub struct RowWise;
+
+/// Each column is a number with its most significant digit on top, columns are read
+/// right to left (problem 2).
+pub struct ColumnsRightToLeft;
+
+/// Same as [`ColumnsRightToLeft`], but columns are read left to right.
+pub struct ColumnsLeftToRight;
+
+/// Each column is a number with its most significant digit at the bottom, columns are
+/// read right to left.
+pub struct BottomToTop;
+
+const READING_ORDERS: [&dyn ReadingOrder; 4] = [
+    &RowWise,
+    &ColumnsRightToLeft,
+    &ColumnsLeftToRight,
+    &BottomToTop,
+];
+
+fn reading_order_by_name(name: &str) -> Option<&'static dyn ReadingOrder> {
+    READING_ORDERS
+        .into_iter()
+        .find(|order| order.name() == name)
+}
+
+fn parse_digits(digits: &str) -> u64 {
+    digits.parse().unwrap()
+}
+
+fn read_columns(
+    operands: &[Operand],
+    columns: impl Iterator<Item = usize>,
+    bottom_up: bool,
+) -> Vec<u64> {
+    columns
+        .map(|col| {
+            let digits = operands.iter().map(|operand| operand.raw_operand[col]);
+            let digits: String = if bottom_up {
+                digits.rev().filter(|c| *c != ' ').collect()
+            } else {
+                digits.filter(|c| *c != ' ').collect()
+            };
+            digits
+        })
+        // a column may hold only the operator, it doesn't form an operand
+        .filter(|digits| !digits.is_empty())
+        .map(|digits| parse_digits(&digits))
+        .collect()
+}
+
+impl ReadingOrder for RowWise {
+    fn name(&self) -> &'static str {
+        "rows"
+    }
+
+    fn read_operands(&self, operands: &[Operand]) -> Vec<u64> {
+        operands
+            .iter()
+            .map(|operand| operand.raw_operand.iter().filter(|c| **c != ' ').collect())
+            .map(|s: String| parse_digits(&s))
+            .collect()
+    }
+}
+
+impl ReadingOrder for ColumnsRightToLeft {
+    fn name(&self) -> &'static str {
+        "columns-rtl"
+    }
+
+    fn read_operands(&self, operands: &[Operand]) -> Vec<u64> {
+        read_columns(operands, (0..operands[0].raw_operand.len()).rev(), false)
+    }
+}
+
+impl ReadingOrder for ColumnsLeftToRight {
+    fn name(&self) -> &'static str {
+        "columns-ltr"
+    }
+
+    fn read_operands(&self, operands: &[Operand]) -> Vec<u64> {
+        read_columns(operands, 0..operands[0].raw_operand.len(), false)
+    }
+}
+
+impl ReadingOrder for BottomToTop {
+    fn name(&self) -> &'static str {
+        "bottom-to-top"
+    }
+
+    fn read_operands(&self, operands: &[Operand]) -> Vec<u64> {
+        read_columns(operands, (0..operands[0].raw_operand.len()).rev(), true)
+    }
+}
+
+fn sum_problems(problem_sheet: &ProblemSheet, reading_order: &dyn ReadingOrder) -> u64 {
     let mut sum = 0;
     for problem in &problem_sheet.problems {
-        sum += problem.evaluate(&mut transformer);
+        sum += problem.evaluate(reading_order);
     }
     sum
 }
 
 fn run_problem_1(problem_sheet: &ProblemSheet) -> u64 {
-    sum_problems(problem_sheet, |vec| {
-        vec.iter()
-            .map(|operand| operand.raw_operand.iter().filter(|c| **c != ' ').collect())
-            .map(|s: String| s.parse().unwrap())
-            .collect()
-    })
+    sum_problems(problem_sheet, &RowWise)
 }
 
 fn run_problem_2(problem_sheet: &ProblemSheet) -> u64 {
-    let transformer = |vec: &Vec<Operand>| -> Vec<u64> {
-        let mut operands = vec![String::new(); vec[0].raw_operand.len()];
-
-        operands.iter_mut().enumerate().for_each(|(operand_idx, operand)| {
-            vec.iter().for_each(|v| {
-                let c = v.raw_operand[operand_idx];
-                if c != ' ' {
-                    operand.push(c);
+    sum_problems(problem_sheet, &ColumnsRightToLeft)
+}
+
+fn main() {
+    let mut options = ParseOptions::default();
+    let mut input_file = String::from("day6.txt");
+    let mut reading_orders = Vec::new();
+    let mut render = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tab-width" => {
+                options.tab_width = args
+                    .next()
+                    .and_then(|w| w.parse().ok())
+                    .expect("--tab-width needs a positive number")
+            }
+            "--render" => {
+                render = match args.next().as_deref() {
+                    Some("keep") => Some(Alignment::Keep),
+                    Some("left") => Some(Alignment::Left),
+                    Some("right") => Some(Alignment::Right),
+                    other => panic!("--render expects keep, left or right, got {other:?}"),
                 }
-            });
-        });
+            }
+            "--generate" => {
+                let seed = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .expect("--generate needs a numeric seed");
+                let generated =
+                    generate_worksheet(&mut Rng::new(seed), &GeneratorConfig::default());
+                print!("{}", generated.sheet.render(Alignment::Keep));
+                eprintln!("problem 1 {}", generated.row_answer);
+                eprintln!("problem 2 {}", generated.column_answer);
+                return;
+            }
+            "--order" => {
+                let name = args.next().unwrap_or_default();
+                let order = reading_order_by_name(&name).unwrap_or_else(|| {
+                    let known: Vec<&str> = READING_ORDERS.iter().map(|o| o.name()).collect();
+                    panic!("unknown reading order {name:?}, expected one of {known:?}")
+                });
+                reading_orders.push(order);
+            }
+            other => input_file = other.to_string(),
+        }
+    }
 
-        operands.iter().rev().map(|o| o.parse().unwrap()).collect()
+    let sheet = match ProblemSheet::new(&read_to_string(&input_file).unwrap(), &options) {
+        Ok(sheet) => sheet,
+        Err(e) => {
+            eprintln!("{input_file}: {e}");
+            std::process::exit(1);
+        }
     };
-    sum_problems(problem_sheet, transformer)
-}
 
-fn main() {
-    let sheet = ProblemSheet::new(&read_to_string("day6.txt").unwrap());
-    println!("problem 1 {}", run_problem_1(&sheet));
-    println!("problem 2 {}", run_problem_2(&sheet));
+    if let Some(alignment) = render {
+        print!("{}", sheet.render(alignment));
+        return;
+    }
+    if reading_orders.is_empty() {
+        println!("problem 1 {}", run_problem_1(&sheet));
+        println!("problem 2 {}", run_problem_2(&sheet));
+    }
+    for order in reading_orders {
+        println!("{} {}", order.name(), sum_problems(&sheet, order));
+    }
 }
 
 #[cfg(test)]
-
 mod tests {
-    use crate::{Operand, Problem, ProblemSheet};
+    use crate::{
+        Alignment, BottomToTop, ColumnsLeftToRight, ColumnsRightToLeft, GeneratorConfig, Operand,
+        Operator, ParseOptions, Problem, ProblemSheet, READING_ORDERS, ReadingOrder, RowWise,
+        SheetError, generate_worksheet, reading_order_by_name, run_problem_1, run_problem_2,
+        sum_problems,
+    };
     use aoc_2025::assets::read_to_string;
+    use aoc_2025::rng::Rng;
+
+    impl ProblemSheet {
+        fn with_defaults(raw_problem: &str) -> Result<ProblemSheet, SheetError> {
+            Self::new(raw_problem, &ParseOptions::default())
+        }
+    }
 
     impl Operand {
         fn new_with_operands(operands: &str) -> Operand {
@@ -179,12 +713,13 @@ mod tests {
 
     #[test]
     fn test_parse_problems() {
-        let sheet = ProblemSheet::new(&read_to_string("day6_example.txt").unwrap());
+        let sheet =
+            ProblemSheet::with_defaults(&read_to_string("day6_example.txt").unwrap()).unwrap();
         assert_eq!(sheet.problems.len(), 4);
         assert_eq!(
             sheet.problems[0],
             Problem::new(
-                '*',
+                Operator::Multiply,
                 vec![
                     Operand::new_with_operands("123"),
                     Operand::new_with_operands(" 45"),
@@ -196,7 +731,7 @@ mod tests {
         assert_eq!(
             sheet.problems[1],
             Problem::new(
-                '+',
+                Operator::Plus,
                 vec![
                     Operand::new_with_operands("328"),
                     Operand::new_with_operands("64 "),
@@ -207,7 +742,7 @@ mod tests {
         assert_eq!(
             sheet.problems[2],
             Problem::new(
-                '*',
+                Operator::Multiply,
                 vec![
                     Operand::new_with_operands(" 51"),
                     Operand::new_with_operands("387"),
@@ -218,7 +753,7 @@ mod tests {
         assert_eq!(
             sheet.problems[3],
             Problem::new(
-                '+',
+                Operator::Plus,
                 vec![
                     Operand::new_with_operands("64 "),
                     Operand::new_with_operands("23 "),
@@ -227,4 +762,167 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_examples() {
+        let sheet =
+            ProblemSheet::with_defaults(&read_to_string("day6_example.txt").unwrap()).unwrap();
+        assert_eq!(run_problem_1(&sheet), 4277556);
+        assert_eq!(run_problem_2(&sheet), 3263827);
+    }
+
+    #[test]
+    fn test_reading_orders() {
+        let operands = vec![
+            Operand::new_with_operands("123"),
+            Operand::new_with_operands(" 45"),
+            Operand::new_with_operands("  6"),
+        ];
+        assert_eq!(RowWise.read_operands(&operands), vec![123, 45, 6]);
+        assert_eq!(
+            ColumnsRightToLeft.read_operands(&operands),
+            vec![356, 24, 1]
+        );
+        assert_eq!(
+            ColumnsLeftToRight.read_operands(&operands),
+            vec![1, 24, 356]
+        );
+        assert_eq!(BottomToTop.read_operands(&operands), vec![653, 42, 1]);
+
+        let sheet =
+            ProblemSheet::with_defaults(&read_to_string("day6_example.txt").unwrap()).unwrap();
+        assert_eq!(
+            sum_problems(&sheet, &ColumnsLeftToRight),
+            run_problem_2(&sheet)
+        );
+        assert_eq!(
+            sum_problems(&sheet, &BottomToTop),
+            653 * 42 + (8 + 842 + 963) + 571 * 185 * 23 + (4 + 134 + 326)
+        );
+
+        for order in READING_ORDERS {
+            assert_eq!(
+                reading_order_by_name(order.name()).unwrap().name(),
+                order.name()
+            );
+        }
+        assert!(reading_order_by_name("diagonal").is_none());
+    }
+
+    #[test]
+    fn test_layout_tolerance() {
+        let example = read_to_string("day6_example.txt").unwrap();
+        let expected = ProblemSheet::with_defaults(&example).unwrap().problems;
+
+        let crlf = example.replace('\n', "   \r\n");
+        assert_eq!(
+            ProblemSheet::with_defaults(&crlf).unwrap().problems,
+            expected
+        );
+
+        let operator_first = "*   +\n123 328\n 45 64\n  6 98\n";
+        let sheet = ProblemSheet::with_defaults(operator_first).unwrap();
+        assert_eq!(sheet.problems[0].operator, Operator::Multiply);
+        assert_eq!(
+            sheet.problems[1].operands[2],
+            Operand::new_with_operands("98 ")
+        );
+
+        let tabbed = "12\t3\n3\t4\n+\t*";
+        let sheet = ProblemSheet::new(tabbed, &ParseOptions { tab_width: 4 }).unwrap();
+        assert_eq!(run_problem_1(&sheet), 12 + 3 + 3 * 4);
+    }
+
+    #[test]
+    fn test_differing_operand_counts() {
+        let sheet = ProblemSheet::with_defaults("12 3\n5\n+  *\n").unwrap();
+        assert_eq!(sheet.problems[0].operands.len(), 2);
+        assert_eq!(
+            sheet.problems[1].operands,
+            vec![Operand::new_with_operands("3")]
+        );
+        assert_eq!(run_problem_1(&sheet), 12 + 5 + 3);
+        assert_eq!(run_problem_2(&sheet), 15 + 2 + 3);
+    }
+
+    #[test]
+    fn test_layout_errors() {
+        assert_eq!(
+            ProblemSheet::with_defaults(" \n\n").unwrap_err(),
+            SheetError::EmptySheet
+        );
+        assert_eq!(
+            ProblemSheet::with_defaults("1 2\n3 4").unwrap_err(),
+            SheetError::MissingOperatorRow
+        );
+        assert_eq!(
+            ProblemSheet::with_defaults("+\n1\n*").unwrap_err(),
+            SheetError::MultipleOperatorRows {
+                first_line: 1,
+                second_line: 3
+            }
+        );
+        assert_eq!(
+            ProblemSheet::with_defaults("12 3\n4x 5\n+  *").unwrap_err(),
+            SheetError::UnexpectedCharacter {
+                line: 2,
+                column: 2,
+                found: 'x'
+            }
+        );
+        assert_eq!(
+            ProblemSheet::with_defaults("12 3\n+  -").unwrap_err(),
+            SheetError::UnknownOperator {
+                line: 2,
+                column: 4,
+                symbol: '-'
+            }
+        );
+        assert_eq!(
+            ProblemSheet::with_defaults("12 3\n+").unwrap_err(),
+            SheetError::MissingOperator { line: 2, column: 4 }
+        );
+        assert_eq!(
+            ProblemSheet::with_defaults("123\n+ *").unwrap_err(),
+            SheetError::MultipleOperators { line: 2, column: 3 }
+        );
+        assert_eq!(
+            ProblemSheet::with_defaults("1\n+ *").unwrap_err(),
+            SheetError::MissingOperands { column: 3 }
+        );
+    }
+
+    #[test]
+    fn test_render_example() {
+        let example = read_to_string("day6_example.txt").unwrap();
+        let sheet = ProblemSheet::with_defaults(&example).unwrap();
+        assert_eq!(sheet.render(Alignment::Keep), example);
+        assert_eq!(
+            sheet.render(Alignment::Right),
+            "123 328  51  64\n 45  64 387  23\n  6  98 215 314\n*   +   *   +\n"
+        );
+        assert_eq!(
+            sheet.render(Alignment::Left),
+            "123 328 51  64\n45  64  387 23\n6   98  215 314\n*   +   *   +\n"
+        );
+    }
+
+    #[test]
+    fn test_generated_round_trip() {
+        let mut rng = Rng::new(2025);
+        for _ in 0..200 {
+            let generated = generate_worksheet(&mut rng, &GeneratorConfig::default());
+            let text = generated.sheet.render(Alignment::Keep);
+            let parsed = ProblemSheet::with_defaults(&text).unwrap();
+            assert_eq!(parsed, generated.sheet, "{text}");
+            assert_eq!(parsed.render(Alignment::Keep), text);
+            assert_eq!(run_problem_1(&parsed), generated.row_answer, "{text}");
+            assert_eq!(run_problem_2(&parsed), generated.column_answer, "{text}");
+
+            for alignment in [Alignment::Left, Alignment::Right] {
+                let realigned = ProblemSheet::with_defaults(&parsed.render(alignment)).unwrap();
+                assert_eq!(run_problem_1(&realigned), generated.row_answer);
+            }
+        }
+    }
 }
@@ -100,7 +100,7 @@ fn run_problem_1(
                 &mut circuits,
             );
         });
-    circuits.sort_by(|left, right| right.len().cmp(&left.len()));
+    circuits.sort_by_key(|circuit| std::cmp::Reverse(circuit.len()));
     // dbg!(&circuits);
     circuits
         .iter()
@@ -126,10 +126,9 @@ fn run_problem_2(
                 box_2,
                 &mut junction_box_to_circuits_map,
                 &mut circuits,
-            ) {
-                if merged_circuit.len() == junction_boxes.len() {
-                    result = Some(box_1.x * box_2.x);
-                }
+            ) && merged_circuit.len() == junction_boxes.len()
+            {
+                result = Some(box_1.x * box_2.x);
             }
         });
 
@@ -138,7 +137,7 @@ fn run_problem_2(
 
 fn main() {
     let data = read_to_string("day8.txt").unwrap();
-    let junction_boxes: Vec<JunctionBox> = data.lines().map(|l| JunctionBox::new(&l)).collect();
+    let junction_boxes: Vec<JunctionBox> = data.lines().map(JunctionBox::new).collect();
     let mut distance_map = BTreeMap::new();
     let boxes_len = junction_boxes.len();
     junction_boxes
@@ -1 +1,2 @@
 pub mod assets;
+pub mod rng;